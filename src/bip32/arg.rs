@@ -1,27 +1,36 @@
+use crate::utils::{KeyInput, SecretString, SecretXpriv, WalletSecrets, nfkd_secret};
 use artimonist::bitcoin::bip32::DerivationPath;
 use artimonist::{Mnemonic, Xpriv, Xpub};
 
 #[derive(clap::Parser, Debug)]
 pub struct Bip32Command {
-    /// Mnemonic phrase or Master key
-    #[clap(name = "MNEMONIC|MASTER_KEY")]
-    pub key: MasterKey,
+    /// Mnemonic phrase or Master key, prompt with masked input if omitted
+    #[clap(
+        name = "MNEMONIC|MASTER_KEY",
+        env = "ARTIMONIST_KEY",
+        hide_env_values = true
+    )]
+    pub key: Option<MasterKey>,
 
     /// Derivation path
     pub path: Option<DerivationPath>,
 
-    /// Password as salt
-    #[clap(hide = true, long)]
-    pub password: Option<String>,
+    /// Mnemonic or master key input channels
+    #[clap(flatten)]
+    pub key_input: KeyInput,
+
+    /// Passphrase and encryption password
+    #[clap(flatten)]
+    pub secrets: WalletSecrets,
 }
 
-/// Master key or Mnemonic string
+/// Master key or Mnemonic string, wiped on drop
 #[derive(Debug, Clone)]
 pub enum MasterKey {
     /// Mnemonic phrase
-    Mnemonic(Mnemonic),
+    Mnemonic(SecretString),
     /// Extended private key
-    Xpriv(Xpriv),
+    Xpriv(SecretXpriv),
     /// Extended public key
     Xpub(Xpub),
 }
@@ -31,21 +40,21 @@ impl std::str::FromStr for MasterKey {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.starts_with("xprv") {
-            Ok(MasterKey::Xpriv(Xpriv::from_str(s)?))
+            Ok(MasterKey::Xpriv(SecretXpriv::new(Xpriv::from_str(s)?)))
         } else if s.starts_with("xpub") {
             Ok(MasterKey::Xpub(Xpub::from_str(s)?))
         } else {
-            Ok(MasterKey::Mnemonic(Mnemonic::from_str(s)?))
+            // wordlists are NFKD, as the words of a mnemonic must be
+            let mnemonic = nfkd_secret(s);
+            Mnemonic::from_str(&mnemonic)?;
+            Ok(MasterKey::Mnemonic(mnemonic))
         }
     }
 }
 
 impl Bip32Command {
-    pub fn is_mnemonic(&self) -> bool {
-        matches!(self.key, MasterKey::Mnemonic(_))
-    }
     pub fn is_xpub(&self) -> bool {
-        matches!(self.key, MasterKey::Xpub(_))
+        matches!(self.key, Some(MasterKey::Xpub(_)))
     }
 }
 
@@ -1,29 +1,30 @@
 use super::arg::{MasterKey, inquire_derive_path};
-use crate::{Execute, utils::inquire_password};
+use crate::Execute;
+use crate::utils::{SecretString, SecretXpriv, require_input};
 use artimonist::bitcoin::{self, Address, bip32::DerivationPath};
 use artimonist::{BIP38, BIP39, Xpriv, Xpub};
 use std::io::Write;
 
 impl Execute for super::arg::Bip32Command {
     fn execute(&mut self) -> anyhow::Result<()> {
+        let key = self.key_input.master_key(&mut self.key)?.clone();
         let path = match &self.path {
             Some(p) => p.clone(),
-            None => inquire_derive_path(self.is_xpub())?,
-        };
-
-        let password = match (self.is_xpub(), &self.password) {
-            (false, Some(p)) => p.clone(),
-            (false, None) => inquire_password(self.is_mnemonic())?,
-            (true, _) => String::new(), // Xpub does not require a password
+            None => {
+                require_input("derivation path", "<PATH>")?;
+                inquire_derive_path(self.is_xpub())?
+            }
         };
 
-        match &self.key {
+        match key {
             MasterKey::Mnemonic(mnemonic) => {
-                let master = Xpriv::from_mnemonic(&mnemonic.to_string(), &password)?;
-                derive_xprv(&master, &path, &password)
+                let passphrase = self.secrets.passphrase()?;
+                let master = SecretXpriv::new(Xpriv::from_mnemonic(&mnemonic, &passphrase)?);
+                derive_xprv(&master, &path, &self.secrets.encryption()?)
             }
-            MasterKey::Xpriv(xprv) => derive_xprv(xprv, &path, &password),
-            MasterKey::Xpub(xpub) => derive_xpub(xpub, &path),
+            MasterKey::Xpriv(xprv) => derive_xprv(&xprv, &path, &self.secrets.encryption()?),
+            // Xpub does not require a password
+            MasterKey::Xpub(xpub) => derive_xpub(&xpub, &path),
         }
     }
 }
@@ -34,12 +35,16 @@ fn derive_xprv(master: &Xpriv, path: &DerivationPath, password: &str) -> anyhow:
     writeln!(f, "Derivation path: [m/{path}]")?;
 
     let secp = bitcoin::secp256k1::Secp256k1::default();
-    let xprv = master.derive_priv(&secp, &path)?;
+    let xprv = SecretXpriv::new(master.derive_priv(&secp, &path)?);
     let xpub = Xpub::from_priv(&secp, &xprv);
-    writeln!(f, "Extended private key: {xprv}")?;
+    writeln!(
+        f,
+        "Extended private key: {}",
+        SecretString::new(xprv.to_string()).as_str()
+    )?;
     writeln!(f, "Extended public key: {xpub}")?;
 
-    let (pub_key, priv_wif) = (xpub.to_pub(), xprv.to_priv().to_string());
+    let (pub_key, priv_wif) = (xpub.to_pub(), SecretString::new(xprv.to_priv().to_string()));
     writeln!(f, "Private key: {}", priv_wif.bip38_encrypt(password)?)?;
     writeln!(f, "Public key: {pub_key}")?;
 
@@ -0,0 +1,308 @@
+//! aezeed cipher seeds of LND: the entropy and birthday of a wallet enciphered by AEZ
+//! under a passphrase, encoded as 24 words of the BIP39 English wordlist.
+//!
+//! <https://github.com/lightningnetwork/lnd/tree/master/aezeed>
+
+use crate::utils::SecretString;
+use aes::Block;
+use anyhow::anyhow;
+use artimonist::Language;
+use blake2::{Blake2b, Digest, digest::consts::U48};
+use zeroize::Zeroize;
+
+/// Cipher seed version of LND
+const VERSION: u8 = 0;
+
+/// Passphrase of the seeds created without one
+const DEFAULT_PASSPHRASE: &str = "aezeed";
+
+/// Genesis block time, birthdays are the days since
+pub const GENESIS_TIME: u32 = 1231006505;
+
+/// Scrypt cost of the passphrase key of version 0: 2^15 rounds
+const LOG_N: u8 = 15;
+
+/// Mnemonic of the cipher seed of `entropy` and `salt`, `birthday` days after the genesis block.
+pub fn cipher_seed(
+    entropy: &[u8; 16],
+    salt: &[u8; 5],
+    birthday: u16,
+    passphrase: &str,
+) -> anyhow::Result<SecretString> {
+    encipher(entropy, salt, birthday, passphrase, LOG_N)
+}
+
+/// Cipher seed of the passphrase key of scrypt cost 2^`log_n`.
+fn encipher(
+    entropy: &[u8; 16],
+    salt: &[u8; 5],
+    birthday: u16,
+    passphrase: &str,
+    log_n: u8,
+) -> anyhow::Result<SecretString> {
+    let mut plain = [0u8; 19];
+    plain[0] = VERSION;
+    plain[1..3].copy_from_slice(&birthday.to_be_bytes());
+    plain[3..].copy_from_slice(entropy);
+
+    let passphrase = match passphrase {
+        "" => DEFAULT_PASSPHRASE,
+        p => p,
+    };
+    let mut key = [0u8; 32];
+    let params = scrypt::Params::new(log_n, 8, 1, 32).map_err(|e| anyhow!("{e}"))?;
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key).map_err(|e| anyhow!("{e}"))?;
+    let mut ad = [VERSION; 6];
+    ad[1..].copy_from_slice(salt);
+
+    // enciphered seed of the version, ciphertext, salt and checksum
+    let mut seed = [0u8; 33];
+    seed[0] = VERSION;
+    let aez = Aez::new(&key);
+    key.zeroize();
+    aez.encrypt(&ad, &plain, 4, &mut seed[1..24]);
+    plain.zeroize();
+    seed[24..29].copy_from_slice(salt);
+    let checksum = crc32c(&seed[..29]);
+    seed[29..].copy_from_slice(&checksum.to_be_bytes());
+
+    // 11 bits a word, the most significant first
+    let mut words = Vec::with_capacity(24);
+    for w in 0..24 {
+        let index = (0..11).fold(0, |n, b| {
+            let bit = w * 11 + b;
+            n << 1 | (seed[bit / 8] >> (7 - bit % 8) & 1) as usize
+        });
+        words.push(Language::English.word_at(index).expect("11 bits"));
+    }
+    seed.zeroize();
+    Ok(SecretString::new(words.join(" ")))
+}
+
+/// AEZ v5 of a key, only as far as aezeed enciphers by it:
+/// no nonce, and messages of less than 32 bytes.
+struct Aez {
+    /// Subkeys I, J and L of the extracted key
+    keys: [Block; 3],
+    /// 2I
+    i2: Block,
+    /// J, 2J, 4J
+    j: [Block; 3],
+    /// 0L to 7L
+    l: [Block; 8],
+}
+
+impl Aez {
+    fn new(key: &[u8]) -> Self {
+        let mut extracted = Blake2b::<U48>::digest(key);
+        let subkey = |n: usize| Block::clone_from_slice(&extracted[n * 16..n * 16 + 16]);
+        let keys = [subkey(0), subkey(1), subkey(2)];
+        extracted.zeroize();
+        let j2 = double(&keys[1]);
+        let mut l = [Block::default(); 8];
+        l[1] = keys[2];
+        for i in 2..8 {
+            l[i] = match i % 2 {
+                0 => double(&l[i / 2]),
+                _ => xor(&l[i - 1], &l[1]),
+            };
+        }
+        Self {
+            keys,
+            i2: double(&keys[0]),
+            j: [keys[1], j2, double(&j2)],
+            l,
+        }
+    }
+
+    /// AES4 of the round keys J, I, L and 0, all rounds full ones.
+    fn aes4(&self, x: &Block) -> Block {
+        let mut block = *x;
+        for key in [
+            &self.keys[1],
+            &self.keys[0],
+            &self.keys[2],
+            &Block::default(),
+        ] {
+            aes::hazmat::cipher_round(&mut block, key);
+        }
+        block
+    }
+
+    /// Tweakable block cipher E^{j,i} of `i` below 9: AES4 of X ^ jJ ^ 2^⌈i/8⌉I ^ (i mod 8)L.
+    fn e(&self, j: usize, i: usize, x: &Block) -> Block {
+        let jj = match j {
+            0 => Block::default(),
+            _ => mult(j, &self.j[0]),
+        };
+        let ii = match i {
+            0 => self.keys[0],
+            _ => self.i2,
+        };
+        self.aes4(&xor(&xor(x, &jj), &xor(&ii, &self.l[i % 8])))
+    }
+
+    /// AEZ-hash of the tag length and the associated data, the nonce being empty.
+    fn hash(&self, tau: usize, ad: &[&[u8]]) -> Block {
+        let mut block = Block::default();
+        block[12..].copy_from_slice(&(tau as u32 * 8).to_be_bytes());
+        let mut sum = self.e(3, 1, &block);
+        // the empty nonce, then each associated data
+        for (k, data) in std::iter::once(&[][..])
+            .chain(ad.iter().copied())
+            .enumerate()
+        {
+            let chunks = data.chunks_exact(16);
+            let rest = chunks.remainder();
+            for (i, chunk) in chunks.enumerate() {
+                sum = xor(&sum, &self.e(4 + k, i + 1, Block::from_slice(chunk)));
+            }
+            if !rest.is_empty() || data.is_empty() {
+                let mut block = Block::default();
+                block[..rest.len()].copy_from_slice(rest);
+                block[rest.len()] = 0x80;
+                sum = xor(&sum, &self.e(4 + k, 0, &block));
+            }
+        }
+        sum
+    }
+
+    /// Encrypt a message of 16 to 31 bytes with a tag of `tau` zero bytes into `out`.
+    fn encrypt(&self, ad: &[u8], message: &[u8], tau: usize, out: &mut [u8]) {
+        let delta = self.hash(tau, &[ad]);
+        let mut x = message.to_vec();
+        x.resize(message.len() + tau, 0);
+        self.tiny(&delta, &x, out);
+        x.zeroize();
+    }
+
+    /// AEZ-tiny of a message of 16 to 31 bytes: a Feistel network of 8 rounds.
+    fn tiny(&self, delta: &Block, input: &[u8], out: &mut [u8]) {
+        let len = input.len();
+        debug_assert!((16..32).contains(&len));
+        let half = len.div_ceil(2);
+        let (mut left, mut right) = (Block::default(), Block::default());
+        left[..half].copy_from_slice(&input[..half]);
+        right[..half].copy_from_slice(&input[len / 2..len / 2 + half]);
+        // halves of an odd length end in a nibble, shift the right one to the front
+        let (mask, pad) = match len % 2 {
+            1 => {
+                for k in 0..len / 2 {
+                    right[k] = right[k] << 4 | right[k + 1] >> 4;
+                }
+                right[len / 2] <<= 4;
+                (0xf0, 0x08)
+            }
+            _ => (0x00, 0x80),
+        };
+        let round = |half_block: &Block, j: u8| {
+            let mut block = Block::default();
+            block[..half].copy_from_slice(&half_block[..half]);
+            block[len / 2] = block[len / 2] & mask | pad;
+            let mut block = xor(&block, delta);
+            block[15] ^= j;
+            self.e(0, 6, &block)
+        };
+        for j in (0..8).step_by(2) {
+            left = xor(&left, &round(&right, j));
+            right = xor(&right, &round(&left, j + 1));
+        }
+
+        let mut buf = [0u8; 32];
+        buf[..len / 2].copy_from_slice(&right[..len / 2]);
+        buf[len / 2..len / 2 + half].copy_from_slice(&left[..half]);
+        if len % 2 == 1 {
+            for k in (len / 2 + 1..len).rev() {
+                buf[k] = buf[k] >> 4 | buf[k - 1] << 4;
+            }
+            buf[len / 2] = left[0] >> 4 | right[len / 2] & 0xf0;
+        }
+        out.copy_from_slice(&buf[..len]);
+        buf.zeroize();
+        left.zeroize();
+        right.zeroize();
+    }
+}
+
+fn xor(a: &Block, b: &Block) -> Block {
+    Block::from_iter(a.iter().zip(b.iter()).map(|(x, y)| x ^ y))
+}
+
+/// Doubling in GF(2^128), big-endian.
+fn double(block: &Block) -> Block {
+    let mut result = Block::default();
+    for i in 0..16 {
+        result[i] = block[i] << 1 | block.get(i + 1).map_or(0, |b| b >> 7);
+    }
+    if block[0] & 0x80 != 0 {
+        result[15] ^= 0x87;
+    }
+    result
+}
+
+/// Product of `n` and a block in GF(2^128).
+fn mult(n: usize, block: &Block) -> Block {
+    let mut result = Block::default();
+    let mut power = *block;
+    let mut n = n;
+    while n > 0 {
+        if n & 1 == 1 {
+            result = xor(&result, &power);
+        }
+        power = double(&power);
+        n >>= 1;
+    }
+    result
+}
+
+/// CRC-32C (Castagnoli).
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0x82f63b78 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod aezeed_test {
+    use super::*;
+
+    /// Entropy and salt of LND's test vectors
+    const ENTROPY: [u8; 16] = [
+        0x81, 0xb6, 0x37, 0xd8, 0x63, 0x59, 0xe6, 0x96, 0x0d, 0xe7, 0x95, 0xe4, 0x1e, 0x0b, 0x4c,
+        0xfd,
+    ];
+    const SALT: &[u8; 5] = b"salt1";
+
+    #[test]
+    fn test_cipher_seed() {
+        // test vectors of LND, of the scrypt cost 16 its tests set
+        let seed = encipher(&ENTROPY, SALT, 0, "", 4).unwrap();
+        assert_eq!(
+            seed.as_str(),
+            "ability liquid travel stem barely drastic pact cupboard apple thrive morning oak feature tissue couch old math inform success suggest drink motion know royal"
+        );
+        let seed = encipher(&ENTROPY, SALT, 3365, "!very_safe_55345_password*", 4).unwrap();
+        assert_eq!(
+            seed.as_str(),
+            "able tree stool crush transfer cloud cross three profit outside hen citizen plate ride require leg siren drum success suggest drink require fiscal upgrade"
+        );
+
+        // the passphrase defaults to "aezeed"
+        let seed = cipher_seed(&ENTROPY, SALT, 0, "").unwrap();
+        assert_eq!(
+            seed.as_str(),
+            cipher_seed(&ENTROPY, SALT, 0, "aezeed").unwrap().as_str()
+        );
+        assert!(seed.as_str().contains(" success suggest drink "));
+    }
+
+    #[test]
+    fn test_crc32c() {
+        assert_eq!(crc32c(b"123456789"), 0xe3069283);
+    }
+}
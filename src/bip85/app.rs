@@ -0,0 +1,155 @@
+//! BIP85 applications beyond the ones of the artimonist library.
+
+use crate::diagram::PwdType;
+use artimonist::bitcoin::bip32::DerivationPath;
+use artimonist::bitcoin::hashes::{Hash, HashEngine, Hmac, HmacEngine, sha512};
+use artimonist::bitcoin::hex::DisplayHex;
+use artimonist::bitcoin::secp256k1::Secp256k1;
+use artimonist::{BIP85, Xpriv};
+use sha3::Shake256;
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use std::str::FromStr;
+use zeroize::Zeroize;
+
+pub(super) const BASE64: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Base85 characters of RFC 1924, as the BIP85 reference encodes
+const BASE85: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+pub trait Bip85App {
+    /// Entropy of `bytes` in hex, for disk encryption and other keys
+    fn bip85_hex(&self, bytes: u32, index: u32) -> anyhow::Result<String>;
+    /// Password of base64 characters
+    fn bip85_base64(&self, length: u32, index: u32) -> anyhow::Result<String>;
+    /// Password of base85 characters
+    fn bip85_base85(&self, length: u32, index: u32) -> anyhow::Result<String>;
+    /// Password of the characters of `pwd_type`
+    fn bip85_password(&self, pwd_type: PwdType, length: u32, index: u32) -> anyhow::Result<String>;
+    /// Rolls of a die of `sides` numbered from 0, comma separated
+    fn bip85_dice(&self, sides: u32, rolls: u32, index: u32) -> anyhow::Result<String>;
+}
+
+impl Bip85App for Xpriv {
+    fn bip85_hex(&self, bytes: u32, index: u32) -> anyhow::Result<String> {
+        if !(16..=64).contains(&bytes) {
+            anyhow::bail!("hex entropy has 16 to 64 bytes");
+        }
+        let mut entropy = entropy(self, &format!("m/83696968'/128169'/{bytes}'/{index}'"))?;
+        let hex = entropy[..bytes as usize].to_lower_hex_string();
+        entropy.zeroize();
+        Ok(hex)
+    }
+
+    fn bip85_base64(&self, length: u32, index: u32) -> anyhow::Result<String> {
+        if !(20..=86).contains(&length) {
+            anyhow::bail!("base64 passwords have 20 to 86 characters");
+        }
+        let mut entropy = entropy(self, &format!("m/83696968'/707764'/{length}'/{index}'"))?;
+        // 6 bits a character, the last one padded by zero bits
+        let mut pwd = String::with_capacity(length as usize);
+        for i in 0..length as usize {
+            let (byte, shift) = (i * 6 / 8, i * 6 % 8);
+            let pair = u16::from_be_bytes([entropy[byte], *entropy.get(byte + 1).unwrap_or(&0)]);
+            pwd.push(BASE64[(pair >> (10 - shift)) as usize & 0x3f] as char);
+        }
+        entropy.zeroize();
+        Ok(pwd)
+    }
+
+    fn bip85_base85(&self, length: u32, index: u32) -> anyhow::Result<String> {
+        if !(10..=80).contains(&length) {
+            anyhow::bail!("base85 passwords have 10 to 80 characters");
+        }
+        let mut entropy = entropy(self, &format!("m/83696968'/707785'/{length}'/{index}'"))?;
+        // 5 characters of each 4 bytes, the most significant first
+        let mut pwd = String::with_capacity(80);
+        for chunk in entropy.chunks(4) {
+            let mut n = u32::from_be_bytes(chunk.try_into().expect("4 bytes"));
+            let mut digits = [0u8; 5];
+            for d in digits.iter_mut().rev() {
+                *d = BASE85[(n % 85) as usize];
+                n /= 85;
+            }
+            pwd.extend(digits.iter().map(|&d| d as char));
+        }
+        pwd.truncate(length as usize);
+        entropy.zeroize();
+        Ok(pwd)
+    }
+
+    fn bip85_password(&self, pwd_type: PwdType, length: u32, index: u32) -> anyhow::Result<String> {
+        match pwd_type {
+            PwdType::Distinct => Ok(self.bip85_pwd(Default::default(), length as usize, index)?),
+            PwdType::Base64 => self.bip85_base64(length, index),
+            PwdType::Base85 => self.bip85_base85(length, index),
+        }
+    }
+
+    fn bip85_dice(&self, sides: u32, rolls: u32, index: u32) -> anyhow::Result<String> {
+        if !(2..1 << 31).contains(&sides) || !(1..=10000).contains(&rolls) {
+            anyhow::bail!("dice have 2 to 2^31-1 sides and 1 to 10000 rolls");
+        }
+        let mut entropy = entropy(
+            self,
+            &format!("m/83696968'/89101'/{sides}'/{rolls}'/{index}'"),
+        )?;
+        // BIP85-DRNG: SHAKE256 of the entropy, rolls out of range are dropped
+        let mut shake = Shake256::default();
+        shake.update(&entropy);
+        entropy.zeroize();
+        let mut reader = shake.finalize_xof();
+        let bits = u32::BITS - (sides - 1).leading_zeros();
+        let bytes = bits.div_ceil(8) as usize;
+        let mut results = Vec::with_capacity(rolls as usize);
+        while results.len() < rolls as usize {
+            let mut buf = [0u8; 8];
+            reader.read(&mut buf[8 - bytes..]);
+            let trial = u64::from_be_bytes(buf) >> (bytes as u32 * 8 - bits);
+            if trial < sides as u64 {
+                results.push(trial.to_string());
+            }
+        }
+        Ok(results.join(","))
+    }
+}
+
+/// BIP85 entropy of a derivation path: HMAC-SHA512 of the derived private key.
+pub(super) fn entropy(master: &Xpriv, path: &str) -> anyhow::Result<[u8; 64]> {
+    let path = DerivationPath::from_str(path)?;
+    let derived = master.derive_priv(&Secp256k1::signing_only(), &path)?;
+    let mut engine = HmacEngine::<sha512::Hash>::new(b"bip-entropy-from-k");
+    let mut key = derived.private_key.secret_bytes();
+    engine.input(&key);
+    key.zeroize();
+    Ok(Hmac::from_engine(engine).to_byte_array())
+}
+
+#[cfg(test)]
+mod app_test {
+    use super::*;
+
+    /// Master key of the BIP85 test vectors
+    const MASTER: &str = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+
+    #[test]
+    fn test_bip85_vectors() {
+        let master = Xpriv::from_str(MASTER).unwrap();
+        assert_eq!(
+            master.bip85_hex(64, 0).unwrap(),
+            "492db4698cf3b73a5a24998aa3e9d7fa96275d85724a91e71aa2d645442f878555d078fd1f1f67e368976f04137b1f7a0d19232136ca50c44614af72b5582a5c"
+        );
+        assert_eq!(master.bip85_base64(21, 0).unwrap(), "dKLoepugzdVJvdL56ogNV");
+        assert_eq!(master.bip85_base85(12, 0).unwrap(), "_s`{TW89)i4`");
+        assert_eq!(master.bip85_dice(6, 10, 0).unwrap(), "1,0,0,2,0,1,5,5,2,4");
+
+        // the longest base64 password ends in padding bits
+        let pwd = master.bip85_base64(86, 0).unwrap();
+        assert_eq!(pwd.len(), 86);
+        assert!(master.bip85_base85(81, 0).is_err());
+        assert!(master.bip85_dice(1 << 31, 1, 0).is_err());
+        assert!(master.bip85_dice(6, 10001, 0).is_err());
+        assert!(master.bip85_hex(15, 0).is_err());
+    }
+}
@@ -0,0 +1,43 @@
+use crate::derive::MasterKey;
+use crate::diagram::GenerateTarget;
+use crate::utils::{KeyInput, SecretString, WalletSecrets};
+use artimonist::Language;
+
+#[derive(clap::Parser, Debug)]
+pub struct Bip85Command {
+    /// Mnemonic phrase or Master key, prompt with masked input if omitted
+    #[clap(
+        name = "MNEMONIC|MASTER_KEY",
+        env = "ARTIMONIST_KEY",
+        hide_env_values = true
+    )]
+    pub key: Option<MasterKey>,
+
+    /// Start index
+    #[clap(short, long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..65536))]
+    pub index: u32,
+
+    /// Amount to generate
+    #[clap(short = 'm', long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(0..65536))]
+    pub amount: u32,
+
+    /// Generation target
+    #[command(flatten)]
+    pub target: GenerateTarget,
+
+    /// Mnemonic language
+    #[clap(long)]
+    pub language: Option<Language>,
+
+    /// Mnemonic or master key input channels
+    #[clap(flatten)]
+    pub key_input: KeyInput,
+
+    /// Passphrase and encryption password
+    #[clap(flatten)]
+    pub secrets: WalletSecrets,
+
+    /// Encryption password of the private keys, once resolved
+    #[clap(skip)]
+    pub encryption: Option<SecretString>,
+}
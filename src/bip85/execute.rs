@@ -0,0 +1,53 @@
+use super::Bip85Command;
+use crate::derive::MasterKey;
+use crate::diagram::{DeriveTargets, GenerateTarget, fingerprint};
+use crate::utils::{SecretString, SecretXpriv, require_input, select_language};
+use artimonist::{BIP39, Language, Xpriv};
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+
+impl crate::Execute for Bip85Command {
+    fn execute(&mut self) -> anyhow::Result<()> {
+        self.target.validate()?;
+        let master = match self.key_input.master_key(&mut self.key)?.clone() {
+            MasterKey::Xpriv(master) => master,
+            MasterKey::Mnemonic(mnemonic) => {
+                let passphrase = self.secrets.passphrase()?;
+                SecretXpriv::new(Xpriv::from_mnemonic(&mnemonic, &passphrase)?)
+            }
+        };
+
+        // choose a mnemonic language if needed
+        if self.has_mnemonic() && self.language.is_none() {
+            require_input("mnemonic language", "--language")?;
+            self.language = Some(select_language(Language::all())?);
+        }
+
+        // private keys are printed encrypted
+        if self.target.wif {
+            self.encryption = Some(self.secrets.encryption()?);
+        }
+
+        let f = &mut BufWriter::new(std::io::stdout());
+        writeln!(f, "Fingerprint: {}", fingerprint(&master))?;
+        self.derive_all(&master, f)
+    }
+}
+
+impl DeriveTargets for Bip85Command {
+    fn target(&self) -> &GenerateTarget {
+        &self.target
+    }
+
+    fn indices(&self) -> Range<u32> {
+        self.index..self.index + self.amount
+    }
+
+    fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    fn encryption(&self) -> Option<&SecretString> {
+        self.encryption.as_ref()
+    }
+}
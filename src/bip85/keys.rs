@@ -0,0 +1,168 @@
+//! SSH, OpenPGP and age keys of BIP85 entropy.
+//!
+//! These applications are Artimonist's own extensions, not part of BIP85: other BIP85
+//! wallets don't derive the same keys. The first 32 bytes of the entropy are the ed25519
+//! seed or the X25519 secret, the OpenPGP encryption subkey takes the last 32.
+
+use super::app::{BASE64, entropy};
+use super::openpgp;
+use crate::utils::SecretString;
+use artimonist::Xpriv;
+use artimonist::bitcoin::bech32::{self, Bech32, Hrp};
+use ed25519_dalek::SigningKey;
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroize;
+
+/// Application numbers of the keys, the ASCII codes of the names as RSA's 828365 of BIP85.
+/// Changing one changes every key derived of it.
+const APP_SSH: u32 = 838372; // "SSH"
+const APP_GPG: u32 = 718071; // "GPG"
+const APP_AGE: u32 = 657169; // "AGE"
+
+/// OpenSSH key pair of ed25519
+pub struct SshKey {
+    /// Line of an `authorized_keys` or `.pub` file
+    pub public: String,
+    /// Unencrypted `OPENSSH PRIVATE KEY` file
+    pub private: SecretString,
+}
+
+/// OpenPGP key of an ed25519 primary key and a cv25519 encryption subkey
+pub struct GpgKey {
+    /// Fingerprint of the primary key in hex
+    pub fingerprint: String,
+    /// Armored `PGP PRIVATE KEY BLOCK` of the keys, the user ID and self-signatures
+    pub private: SecretString,
+}
+
+/// age identity of X25519
+pub struct AgeKey {
+    /// `age1...` recipient to encrypt to
+    pub recipient: String,
+    /// `AGE-SECRET-KEY-1...` identity to decrypt by
+    pub identity: SecretString,
+}
+
+pub trait Bip85Keys {
+    /// SSH key, commented by `comment` in both files
+    fn bip85_ssh(&self, comment: &str, index: u32) -> anyhow::Result<SshKey>;
+    /// OpenPGP key of `user_id`, created at unix time `created`
+    fn bip85_gpg(&self, user_id: &str, created: u32, index: u32) -> anyhow::Result<GpgKey>;
+    /// age identity
+    fn bip85_age(&self, index: u32) -> anyhow::Result<AgeKey>;
+}
+
+impl Bip85Keys for Xpriv {
+    fn bip85_ssh(&self, comment: &str, index: u32) -> anyhow::Result<SshKey> {
+        let mut entropy = entropy(self, &format!("m/83696968'/{APP_SSH}'/{index}'"))?;
+        let key = SigningKey::from_bytes(entropy[..32].try_into().expect("32 bytes"));
+        entropy.zeroize();
+        let public = key.verifying_key().to_bytes();
+
+        let mut blob = vec![];
+        put_string(&mut blob, b"ssh-ed25519");
+        put_string(&mut blob, &public);
+
+        // https://github.com/openssh/openssh-portable/blob/master/PROTOCOL.key
+        let mut private = Vec::with_capacity(256);
+        // the check ints of the public key instead of random ones, so the file is reproducible
+        private.extend(&public[..4]);
+        private.extend(&public[..4]);
+        put_string(&mut private, b"ssh-ed25519");
+        put_string(&mut private, &public);
+        let mut keypair = key.to_keypair_bytes();
+        put_string(&mut private, &keypair);
+        keypair.zeroize();
+        put_string(&mut private, comment.as_bytes());
+        for pad in 1..=(8 - private.len() % 8) % 8 {
+            private.push(pad as u8);
+        }
+
+        let mut data = Vec::with_capacity(512);
+        data.extend(b"openssh-key-v1\0");
+        put_string(&mut data, b"none");
+        put_string(&mut data, b"none");
+        put_string(&mut data, b"");
+        data.extend(1u32.to_be_bytes());
+        put_string(&mut data, &blob);
+        put_string(&mut data, &private);
+        private.zeroize();
+        let text = armor("OPENSSH PRIVATE KEY", "", &data, 70, "");
+        data.zeroize();
+
+        Ok(SshKey {
+            public: format!("ssh-ed25519 {} {comment}", base64(&blob))
+                .trim_end()
+                .to_owned(),
+            private: text,
+        })
+    }
+
+    fn bip85_gpg(&self, user_id: &str, created: u32, index: u32) -> anyhow::Result<GpgKey> {
+        let mut entropy = entropy(self, &format!("m/83696968'/{APP_GPG}'/{index}'"))?;
+        let primary = SigningKey::from_bytes(entropy[..32].try_into().expect("32 bytes"));
+        let mut subkey: [u8; 32] = entropy[32..].try_into().expect("32 bytes");
+        entropy.zeroize();
+        let key = openpgp::transferable_key(&primary, &subkey, user_id, created);
+        subkey.zeroize();
+        Ok(key)
+    }
+
+    fn bip85_age(&self, index: u32) -> anyhow::Result<AgeKey> {
+        let mut entropy = entropy(self, &format!("m/83696968'/{APP_AGE}'/{index}'"))?;
+        let mut secret: [u8; 32] = entropy[..32].try_into().expect("32 bytes");
+        entropy.zeroize();
+        let public = PublicKey::from(&StaticSecret::from(secret));
+        let recipient = bech32::encode::<Bech32>(Hrp::parse("age")?, public.as_bytes())?;
+        let identity = bech32::encode::<Bech32>(Hrp::parse("age-secret-key-")?, &secret)?;
+        secret.zeroize();
+        Ok(AgeKey {
+            recipient,
+            identity: SecretString::new(identity.to_uppercase()),
+        })
+    }
+}
+
+/// Field of an SSH key file: the length and the bytes.
+fn put_string(buf: &mut Vec<u8>, data: &[u8]) {
+    buf.extend((data.len() as u32).to_be_bytes());
+    buf.extend(data);
+}
+
+/// Base64 with padding.
+pub(super) fn base64(data: &[u8]) -> String {
+    let mut text = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => text.push(BASE64[(n >> (18 - 6 * i)) as usize & 0x3f] as char),
+                false => text.push('='),
+            }
+        }
+    }
+    text
+}
+
+/// Base64 of `data` in lines of `width` between the BEGIN and END lines of `label`,
+/// with `header` and `footer` lines around it.
+pub(super) fn armor(
+    label: &str,
+    header: &str,
+    data: &[u8],
+    width: usize,
+    footer: &str,
+) -> SecretString {
+    let encoded = SecretString::new(base64(data));
+    let mut text = String::with_capacity(encoded.as_str().len() * 2);
+    text += &format!("-----BEGIN {label}-----\n{header}");
+    for line in encoded.as_str().as_bytes().chunks(width) {
+        text += std::str::from_utf8(line).expect("base64");
+        text.push('\n');
+    }
+    text += &format!("{footer}-----END {label}-----");
+    SecretString::new(text)
+}
@@ -0,0 +1,142 @@
+//! Seeds of Lightning nodes from BIP85 HEX entropy.
+//!
+//! Core Lightning's `hsm_secret` is the HEX entropy of 32 bytes, the one `--hex 32` shows,
+//! and the rest of its 64 bytes makes the header of the encrypted file reproducible.
+//! LND's aezeed enciphers the HEX entropy of 16 bytes, salted by the next 5.
+
+use super::aezeed;
+use super::app::entropy;
+use crate::utils::SecretString;
+use anyhow::anyhow;
+use artimonist::Xpriv;
+use chacha20::ChaCha20;
+use chacha20::cipher::{KeyIvInit, StreamCipher, StreamCipherSeek, consts::U10};
+use poly1305::Poly1305;
+use poly1305::universal_hash::{KeyInit, UniversalHash};
+use zeroize::{Zeroize, Zeroizing};
+
+pub use aezeed::GENESIS_TIME;
+
+/// Salt of the passphrase of an encrypted `hsm_secret`
+const CLN_SALT: &[u8; 16] = b"c-lightning\0\0\0\0\0";
+
+pub trait Bip85Lightning {
+    /// Core Lightning `hsm_secret`, encrypted as `lightningd --encrypted-hsm` does by `passphrase`
+    fn bip85_hsm_secret(
+        &self,
+        passphrase: Option<&str>,
+        index: u32,
+    ) -> anyhow::Result<Zeroizing<Vec<u8>>>;
+    /// LND aezeed of the wallet born `birthday` days after the genesis block
+    fn bip85_aezeed(
+        &self,
+        birthday: u16,
+        passphrase: &str,
+        index: u32,
+    ) -> anyhow::Result<SecretString>;
+}
+
+impl Bip85Lightning for Xpriv {
+    fn bip85_hsm_secret(
+        &self,
+        passphrase: Option<&str>,
+        index: u32,
+    ) -> anyhow::Result<Zeroizing<Vec<u8>>> {
+        let entropy = Zeroizing::new(entropy(self, &format!("m/83696968'/128169'/32'/{index}'"))?);
+        let secret = &entropy[..32];
+        let Some(passphrase) = passphrase else {
+            return Ok(Zeroizing::new(secret.to_vec()));
+        };
+        let header: &[u8; 24] = entropy[32..56].try_into().expect("24 bytes");
+        encrypt_hsm_secret(secret, header, passphrase)
+    }
+
+    fn bip85_aezeed(
+        &self,
+        birthday: u16,
+        passphrase: &str,
+        index: u32,
+    ) -> anyhow::Result<SecretString> {
+        let entropy = Zeroizing::new(entropy(self, &format!("m/83696968'/128169'/16'/{index}'"))?);
+        let seed: &[u8; 16] = entropy[..16].try_into().expect("16 bytes");
+        let salt: &[u8; 5] = entropy[16..21].try_into().expect("5 bytes");
+        aezeed::cipher_seed(seed, salt, birthday, passphrase)
+    }
+}
+
+/// Encrypted `hsm_secret`: the secret pushed to a secretstream of the argon2id key of `passphrase`,
+/// of libsodium's moderate limits.
+fn encrypt_hsm_secret(
+    secret: &[u8],
+    header: &[u8; 24],
+    passphrase: &str,
+) -> anyhow::Result<Zeroizing<Vec<u8>>> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    let params = argon2::Params::new(256 * 1024, 3, 1, Some(32)).map_err(|e| anyhow!("{e}"))?;
+    argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), CLN_SALT, &mut key[..])
+        .map_err(|e| anyhow!("{e}"))?;
+    Ok(secretstream_push(&key, header, secret))
+}
+
+/// The header and the first message of a libsodium `crypto_secretstream_xchacha20poly1305`
+/// stream of `key`, the message tagged 0 and without associated data.
+fn secretstream_push(key: &[u8; 32], header: &[u8; 24], message: &[u8]) -> Zeroizing<Vec<u8>> {
+    let mut k = chacha20::hchacha::<U10>(key.into(), header[..16].into());
+    // the counter 1, then the rest of the header
+    let mut nonce = [0u8; 12];
+    nonce[0] = 1;
+    nonce[4..].copy_from_slice(&header[16..]);
+    let mut chacha = ChaCha20::new(&k, &nonce.into());
+    k.zeroize();
+
+    let mut poly_key = Zeroizing::new([0u8; 64]);
+    chacha.apply_keystream(&mut poly_key[..]);
+    let mut poly = Poly1305::new(poly_key[..32].into());
+    let mut block = [0u8; 64];
+    chacha.seek(64);
+    chacha.apply_keystream(&mut block);
+    poly.update_padded(&block);
+
+    let mut out = Zeroizing::new(Vec::with_capacity(24 + 1 + message.len() + 16));
+    out.extend(header);
+    out.push(block[0]);
+    let start = out.len();
+    out.extend(message);
+    chacha.seek(128);
+    chacha.apply_keystream(&mut out[start..]);
+    poly.update_padded(&out[start..]);
+    let mut lengths = [0u8; 16];
+    lengths[8..].copy_from_slice(&(64 + message.len() as u64).to_le_bytes());
+    poly.update_padded(&lengths);
+    out.extend(poly.finalize());
+    out
+}
+
+#[cfg(test)]
+mod lightning_test {
+    use super::*;
+    use artimonist::bitcoin::hex::DisplayHex;
+
+    #[test]
+    fn test_secretstream_push() {
+        // as libsodium pulls it: the header, then the tag, ciphertext and MAC
+        let out = secretstream_push(&[1; 32], &[2; 24], &[0x42; 32]);
+        assert_eq!(out.len(), 73);
+        assert_eq!(
+            out[24..].to_lower_hex_string(),
+            "92c19c391e5ecfe3770cdaf2f71ebc05bd8c07a77618e86a58f195ed6c2c1865356f5b6b66245d200b26503e726083d0d9"
+        );
+    }
+
+    #[test]
+    fn test_encrypt_hsm_secret() {
+        // libsodium's crypto_pwhash and crypto_secretstream_xchacha20poly1305_push of the same header
+        let out = encrypt_hsm_secret(&[0x42; 32], &[2; 24], "passphrase").unwrap();
+        assert_eq!(out[..24], [2; 24]);
+        assert_eq!(
+            out[24..].to_lower_hex_string(),
+            "b514cbffb6c6a6cff1920e2c0d9b63c13caf156566c8008739867cc6f169e57adab16420c3cf5d28eb7efd3cefb3741a3b"
+        );
+    }
+}
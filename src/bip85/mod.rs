@@ -0,0 +1,12 @@
+mod aezeed;
+mod app;
+mod arg;
+mod execute;
+mod keys;
+mod lightning;
+mod openpgp;
+
+pub use app::Bip85App;
+pub use arg::Bip85Command;
+pub use keys::Bip85Keys;
+pub use lightning::{Bip85Lightning, GENESIS_TIME};
@@ -0,0 +1,253 @@
+//! Transferable secret keys of OpenPGP (RFC 4880) with the EdDSA and ECDH algorithms of
+//! curve 25519, as GnuPG generates by `--quick-gen-key <USER_ID> future-default`.
+
+use super::keys::{GpgKey, armor};
+use artimonist::bitcoin::hashes::{Hash, HashEngine, sha1, sha256};
+use artimonist::bitcoin::hex::DisplayHex;
+use ed25519_dalek::{Signer, SigningKey};
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroize;
+
+const TAG_SECRET_KEY: u8 = 5;
+const TAG_SECRET_SUBKEY: u8 = 7;
+const TAG_SIGNATURE: u8 = 2;
+const TAG_USER_ID: u8 = 13;
+
+const ALGO_ECDH: u8 = 18;
+const ALGO_EDDSA: u8 = 22;
+const HASH_SHA256: u8 = 8;
+
+/// OID 1.3.6.1.4.1.11591.15.1
+const OID_ED25519: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xda, 0x47, 0x0f, 0x01];
+/// OID 1.3.6.1.4.1.3029.1.5.1
+const OID_CV25519: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0x97, 0x55, 0x01, 0x05, 0x01];
+
+/// Signature of a user ID by its key, with the key flags to certify and sign
+const SIG_POSITIVE_CERT: u8 = 0x13;
+/// Signature of a subkey by the primary key, with the key flags to encrypt
+const SIG_SUBKEY_BINDING: u8 = 0x18;
+
+/// Secret key of `primary`, `user_id` and the encryption subkey of secret `subkey`,
+/// the keys and signatures all created at `created`.
+pub fn transferable_key(
+    primary: &SigningKey,
+    subkey: &[u8; 32],
+    user_id: &str,
+    created: u32,
+) -> GpgKey {
+    // public point in the native form, prefixed by 0x40
+    let mut point = vec![0x40];
+    point.extend(primary.verifying_key().as_bytes());
+    let primary_public = public_key(created, ALGO_EDDSA, OID_ED25519, &point, &[]);
+    let fingerprint = fingerprint(&primary_public);
+
+    // the X25519 secret clamped, then big-endian as GnuPG keeps it
+    let secret = StaticSecret::from(*subkey);
+    let mut point = vec![0x40];
+    point.extend(PublicKey::from(&secret).as_bytes());
+    // KDF of SHA256 and AES128
+    let subkey_public = public_key(created, ALGO_ECDH, OID_CV25519, &point, &[3, 1, 8, 7]);
+    let mut scalar = secret.to_bytes();
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+    scalar.reverse();
+
+    let mut data = Vec::with_capacity(1024);
+    let mut seed = primary.to_bytes();
+    let mut body = secret_key(&primary_public, &seed);
+    seed.zeroize();
+    packet(&mut data, TAG_SECRET_KEY, &body);
+    body.zeroize();
+    packet(&mut data, TAG_USER_ID, user_id.as_bytes());
+    let mut hashed = vec![];
+    hashed.extend(subpacket(27, &[0x03]));
+    // AES256, AES192, AES128, then SHA256, SHA512, SHA384, then ZLIB, BZip2, ZIP
+    hashed.extend(subpacket(11, &[9, 8, 7]));
+    hashed.extend(subpacket(21, &[8, 10, 9]));
+    hashed.extend(subpacket(22, &[2, 3, 1]));
+    // features: modification detection
+    hashed.extend(subpacket(30, &[0x01]));
+    let mut signed = hashed_key(&primary_public);
+    signed.push(0xb4);
+    signed.extend((user_id.len() as u32).to_be_bytes());
+    signed.extend(user_id.as_bytes());
+    let cert = signature(
+        primary,
+        &fingerprint,
+        SIG_POSITIVE_CERT,
+        created,
+        hashed,
+        &signed,
+    );
+    packet(&mut data, TAG_SIGNATURE, &cert);
+
+    let mut body = secret_key(&subkey_public, &scalar);
+    scalar.zeroize();
+    packet(&mut data, TAG_SECRET_SUBKEY, &body);
+    body.zeroize();
+    let mut signed = hashed_key(&primary_public);
+    signed.extend(hashed_key(&subkey_public));
+    // flags to encrypt communications and storage
+    let hashed = subpacket(27, &[0x0c]);
+    let binding = signature(
+        primary,
+        &fingerprint,
+        SIG_SUBKEY_BINDING,
+        created,
+        hashed,
+        &signed,
+    );
+    packet(&mut data, TAG_SIGNATURE, &binding);
+
+    let crc = crc24(&data).to_be_bytes();
+    let footer = format!("={}\n", super::keys::base64(&crc[1..]));
+    let private = armor("PGP PRIVATE KEY BLOCK", "\n", &data, 64, &footer);
+    data.zeroize();
+    GpgKey {
+        fingerprint: fingerprint.to_upper_hex_string(),
+        private,
+    }
+}
+
+/// Body of a version 4 public key packet of an elliptic curve.
+fn public_key(created: u32, algo: u8, oid: &[u8], point: &[u8], kdf: &[u8]) -> Vec<u8> {
+    let mut body = vec![4];
+    body.extend(created.to_be_bytes());
+    body.push(algo);
+    body.push(oid.len() as u8);
+    body.extend(oid);
+    body.extend(mpi(point));
+    body.extend(kdf);
+    body
+}
+
+/// Body of an unencrypted secret key packet.
+fn secret_key(public: &[u8], secret: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(public.len() + 40);
+    body.extend(public);
+    // not protected by a passphrase
+    body.push(0);
+    let mut value = mpi(secret);
+    let checksum = value
+        .iter()
+        .fold(0u16, |sum, &b| sum.wrapping_add(b as u16));
+    body.extend(&value);
+    value.zeroize();
+    body.extend(checksum.to_be_bytes());
+    body
+}
+
+/// Version 4 fingerprint: SHA1 of the public key packet.
+fn fingerprint(public: &[u8]) -> [u8; 20] {
+    sha1::Hash::hash(&hashed_key(public)).to_byte_array()
+}
+
+/// Public key as it is hashed for fingerprints and signatures.
+fn hashed_key(public: &[u8]) -> Vec<u8> {
+    let mut data = vec![0x99];
+    data.extend((public.len() as u16).to_be_bytes());
+    data.extend(public);
+    data
+}
+
+/// Body of a version 4 signature of SHA256 over `signed` and the hashed subpackets,
+/// the creation time and issuer fingerprint prepended to `hashed`.
+fn signature(
+    key: &SigningKey,
+    fingerprint: &[u8; 20],
+    sig_type: u8,
+    created: u32,
+    hashed: Vec<u8>,
+    signed: &[u8],
+) -> Vec<u8> {
+    let mut subpackets = subpacket(2, &created.to_be_bytes());
+    subpackets.extend(subpacket(33, &[&[4], &fingerprint[..]].concat()));
+    subpackets.extend(hashed);
+
+    let mut body = vec![4, sig_type, ALGO_EDDSA, HASH_SHA256];
+    body.extend((subpackets.len() as u16).to_be_bytes());
+    body.extend(subpackets);
+
+    let mut engine = sha256::Hash::engine();
+    engine.input(signed);
+    engine.input(&body);
+    engine.input(&[4, 0xff]);
+    engine.input(&(body.len() as u32).to_be_bytes());
+    let hash = sha256::Hash::from_engine(engine).to_byte_array();
+
+    // issuer key ID, unhashed
+    let unhashed = subpacket(16, &fingerprint[12..]);
+    body.extend((unhashed.len() as u16).to_be_bytes());
+    body.extend(unhashed);
+    body.extend(&hash[..2]);
+    let sig = key.sign(&hash).to_bytes();
+    body.extend(mpi(&sig[..32]));
+    body.extend(mpi(&sig[32..]));
+    body
+}
+
+fn subpacket(kind: u8, data: &[u8]) -> Vec<u8> {
+    let mut sub = vec![data.len() as u8 + 1, kind];
+    sub.extend(data);
+    sub
+}
+
+/// Multiprecision integer: the count of bits, then the bytes without leading zeros.
+fn mpi(data: &[u8]) -> Vec<u8> {
+    let data = &data[data.iter().take_while(|&&b| b == 0).count()..];
+    let bits = match data.first() {
+        Some(b) => data.len() * 8 - b.leading_zeros() as usize,
+        None => 0,
+    };
+    let mut value = Vec::with_capacity(data.len() + 2);
+    value.extend((bits as u16).to_be_bytes());
+    value.extend(data);
+    value
+}
+
+/// Packet of the new format.
+fn packet(buf: &mut Vec<u8>, tag: u8, body: &[u8]) {
+    buf.push(0xc0 | tag);
+    match body.len() {
+        len @ 0..192 => buf.push(len as u8),
+        len @ 192..8384 => {
+            buf.push((((len - 192) >> 8) + 192) as u8);
+            buf.push((len - 192) as u8);
+        }
+        len => {
+            buf.push(0xff);
+            buf.extend((len as u32).to_be_bytes());
+        }
+    }
+    buf.extend(body);
+}
+
+/// Checksum of the ASCII armor.
+fn crc24(data: &[u8]) -> u32 {
+    let mut crc = 0xb704ce_u32;
+    for &b in data {
+        crc ^= (b as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= 0x1864cfb;
+            }
+        }
+    }
+    crc & 0xffffff
+}
+
+#[cfg(test)]
+mod openpgp_test {
+    use super::*;
+
+    #[test]
+    fn test_encoding() {
+        assert_eq!(mpi(&[0, 0x01, 0xff]), [0, 9, 0x01, 0xff]);
+        assert_eq!(mpi(&[0x40, 0]), [0, 15, 0x40, 0]);
+        // checksum of RFC 4880's crc24 code
+        assert_eq!(crc24(b""), 0xb704ce);
+        assert_eq!(crc24(b"123456789"), 0x21cf02);
+    }
+}
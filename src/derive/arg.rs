@@ -1,10 +1,15 @@
+use crate::utils::{KeyInput, SecretString, SecretXpriv, WalletSecrets, nfkd_secret};
 use artimonist::{Mnemonic, Xpriv};
 
 #[derive(clap::Parser, Debug)]
 pub struct DeriveCommand {
-    /// Mnemonic phrase or Master key
-    #[clap(name = "MNEMONIC|MASTER_KEY")]
-    pub key: MasterKey,
+    /// Mnemonic phrase or Master key, prompt with masked input if omitted
+    #[clap(
+        name = "MNEMONIC|MASTER_KEY",
+        env = "ARTIMONIST_KEY",
+        hide_env_values = true
+    )]
+    pub key: Option<MasterKey>,
 
     /// Account start index
     #[clap(short, long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..65536))]
@@ -30,18 +35,30 @@ pub struct DeriveCommand {
     #[clap(long)]
     pub detail: bool,
 
-    /// Password as salt
-    #[clap(hide = true, long)]
-    pub password: Option<String>,
+    /// Export unencrypted private keys for other wallets, in FORMAT of bitcoin wallets
+    #[clap(long, value_name = "FORMAT", conflicts_with_all = ["m23", "m35", "detail"])]
+    pub export_keys: Option<Option<ExportFormat>>,
+
+    /// Skip confirmation before exporting unencrypted private keys
+    #[clap(long, requires = "export_keys")]
+    pub yes: bool,
+
+    /// Mnemonic or master key input channels
+    #[clap(flatten)]
+    pub key_input: KeyInput,
+
+    /// Passphrase and encryption password
+    #[clap(flatten)]
+    pub secrets: WalletSecrets,
 }
 
-/// Master key or Mnemonic string
+/// Master key or Mnemonic string, wiped on drop
 #[derive(Debug, Clone)]
 pub enum MasterKey {
     /// Master key in xprv format
-    Xpriv(Xpriv),
+    Xpriv(SecretXpriv),
     /// Mnemonic phrase
-    Mnemonic(Mnemonic),
+    Mnemonic(SecretString),
 }
 
 impl std::str::FromStr for MasterKey {
@@ -49,9 +66,12 @@ impl std::str::FromStr for MasterKey {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.starts_with("xprv") {
-            Ok(MasterKey::Xpriv(Xpriv::from_str(s)?))
+            Ok(MasterKey::Xpriv(SecretXpriv::new(Xpriv::from_str(s)?)))
         } else {
-            Ok(MasterKey::Mnemonic(Mnemonic::from_str(s)?))
+            // wordlists are NFKD, as the words of a mnemonic must be
+            let mnemonic = nfkd_secret(s);
+            Mnemonic::from_str(&mnemonic)?;
+            Ok(MasterKey::Mnemonic(mnemonic))
         }
     }
 }
@@ -68,6 +88,23 @@ pub struct DerivePath {
     /// Use derive path: m/84'/0'/account'/0/index [p2wpkh]
     #[clap(long)]
     pub bip84: bool,
+    /// Use derive path: m/44'/1237'/account'/0/index [nostr keys of NIP-06]
+    #[clap(long, conflicts_with_all = ["m23", "m35"])]
+    pub nostr: bool,
+    /// Use derive path: m/44'/60'/account'/0/index [EVM address, keystore JSON]
+    #[clap(long, conflicts_with_all = ["m23", "m35"])]
+    pub evm: bool,
+}
+
+/// Private key export format
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Electrum import list: `script-type:KEY`
+    Electrum,
+    /// Bitcoin Core `dumpwallet` file
+    Core,
+    /// Plain WIF private keys
+    Wif,
 }
 
 #[derive(clap::Args, Debug)]
@@ -84,12 +121,12 @@ pub struct MultiSig {
 
 impl DeriveCommand {
     #[inline(always)]
-    pub fn is_mnemonic(&self) -> bool {
-        matches!(self.key, MasterKey::Mnemonic(_))
+    pub fn is_multisig(&self) -> bool {
+        self.multisig.m23 || self.multisig.m35
     }
 
     #[inline(always)]
-    pub fn is_multisig(&self) -> bool {
-        self.multisig.m23 || self.multisig.m35
+    pub fn is_export(&self) -> bool {
+        self.export_keys.is_some()
     }
 }
@@ -0,0 +1,218 @@
+//! Addresses of Ethereum and compatible chains, the private keys encrypted as
+//! keystore files of Web3 Secret Storage version 3.
+//!
+//! <https://eips.ethereum.org/EIPS/eip-55>
+//! <https://ethereum.org/en/developers/docs/data-structures-and-encoding/web3-secret-storage/>
+
+use crate::utils::SecretString;
+use aes::Aes128;
+use anyhow::anyhow;
+use artimonist::Xpriv;
+use artimonist::bitcoin::bip32::DerivationPath;
+use artimonist::bitcoin::hex::DisplayHex;
+use artimonist::bitcoin::secp256k1::{Secp256k1, SecretKey};
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use sha3::{Digest, Keccak256};
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+type DeriveResult<T = ()> = anyhow::Result<T>;
+
+/// Scrypt cost of geth's standard keystore files: 2^18 rounds of blocks of 8, 256 MiB
+const LOG_N: u8 = 18;
+const R: u32 = 8;
+const P: u32 = 1;
+
+/// Keys of `m/44'/60'/<account>'/0/<index>` as derive prints them: the address, then the
+/// keystore JSON of the private key encrypted by `password`, or else the private key in hex.
+pub fn evm_wallet(
+    master: &Xpriv,
+    account: u32,
+    index: u32,
+    password: Option<&str>,
+) -> DeriveResult<SecretString> {
+    let secp = Secp256k1::signing_only();
+    let path = DerivationPath::from_str(&format!("m/44'/60'/{account}'/0/{index}"))?;
+    let secret = master.derive_priv(&secp, &path)?.private_key;
+    let address = address(&secret);
+    Ok(SecretString::new(match password {
+        Some(password) => format!("{address}, {}", keystore(&secret, password)?),
+        None => {
+            let hex = SecretString::new(secret.secret_bytes().to_lower_hex_string());
+            format!("{address}, 0x{}", hex.as_str())
+        }
+    }))
+}
+
+/// Address of the key: the last 20 bytes of the Keccak-256 of its public key, checksummed.
+fn address(secret: &SecretKey) -> String {
+    let public = secret
+        .public_key(&Secp256k1::signing_only())
+        .serialize_uncompressed();
+    let hash = Keccak256::digest(&public[1..]);
+    checksum(&hash[12..].to_lower_hex_string())
+}
+
+/// EIP-55: letters uppercase where the nibble of the hash of the lowercase hex is 8 or above.
+fn checksum(hex: &str) -> String {
+    let hash = Keccak256::digest(hex.as_bytes());
+    let mut address = String::with_capacity(42);
+    address.push_str("0x");
+    for (i, c) in hex.chars().enumerate() {
+        let nibble = hash[i / 2] >> (4 * (1 - i % 2)) & 0x0f;
+        match nibble >= 8 {
+            true => address.push(c.to_ascii_uppercase()),
+            false => address.push(c),
+        }
+    }
+    address
+}
+
+/// Keystore of random salt, IV and ID.
+fn keystore(secret: &SecretKey, password: &str) -> DeriveResult<String> {
+    let mut random = [0u8; 64];
+    getrandom::getrandom(&mut random).map_err(|e| anyhow!("{e}"))?;
+    let salt: &[u8; 32] = random[..32].try_into().expect("32 bytes");
+    let iv: &[u8; 16] = random[32..48].try_into().expect("16 bytes");
+    let id: &[u8; 16] = random[48..].try_into().expect("16 bytes");
+    let params = scrypt::Params::new(LOG_N, R, P, 32).map_err(|e| anyhow!("{e}"))?;
+    encrypt(secret, password, &params, salt, iv, id)
+}
+
+/// Keystore JSON in one line of the scrypt key of `password`.
+fn encrypt(
+    secret: &SecretKey,
+    password: &str,
+    params: &scrypt::Params,
+    salt: &[u8; 32],
+    iv: &[u8; 16],
+    id: &[u8; 16],
+) -> DeriveResult<String> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    scrypt::scrypt(password.as_bytes(), salt, params, &mut key[..]).map_err(|e| anyhow!("{e}"))?;
+    let (ciphertext, mac) = seal(secret, &key, iv);
+
+    // random UUID of version 4
+    let mut id = *id;
+    id[6] = id[6] & 0x0f | 0x40;
+    id[8] = id[8] & 0x3f | 0x80;
+    let id = id.to_lower_hex_string();
+    let id = format!(
+        "{}-{}-{}-{}-{}",
+        &id[..8],
+        &id[8..12],
+        &id[12..16],
+        &id[16..20],
+        &id[20..]
+    );
+
+    let address = address(secret)[2..].to_lowercase();
+    Ok(format!(
+        r#"{{"address":"{address}","crypto":{{"cipher":"aes-128-ctr","cipherparams":{{"iv":"{}"}},"ciphertext":"{}","kdf":"scrypt","kdfparams":{{"dklen":32,"n":{},"p":{},"r":{},"salt":"{}"}},"mac":"{}"}},"id":"{id}","version":3}}"#,
+        iv.to_lower_hex_string(),
+        ciphertext.to_lower_hex_string(),
+        1u32 << params.log_n(),
+        params.p(),
+        params.r(),
+        salt.to_lower_hex_string(),
+        mac.to_lower_hex_string()
+    ))
+}
+
+/// The key encrypted by AES-128-CTR of the first half of the derived key, and the MAC:
+/// the Keccak-256 of the second half and the ciphertext.
+fn seal(secret: &SecretKey, key: &[u8; 32], iv: &[u8; 16]) -> ([u8; 32], [u8; 32]) {
+    let mut ciphertext = secret.secret_bytes();
+    ctr::Ctr128BE::<Aes128>::new(key[..16].into(), iv.into()).apply_keystream(&mut ciphertext);
+    let mut hasher = Keccak256::new();
+    hasher.update(&key[16..]);
+    hasher.update(ciphertext);
+    (ciphertext, hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod evm_test {
+    use super::*;
+    use artimonist::BIP39;
+
+    #[test]
+    fn test_address() {
+        // key of the EIP-155 example
+        let secret = SecretKey::from_slice(&[0x46; 32]).unwrap();
+        assert_eq!(
+            address(&secret),
+            "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"
+        );
+        // test vectors of EIP-55
+        assert_eq!(
+            checksum("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"),
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+        assert_eq!(
+            checksum("fb6916095ca1df60bb79ce92ce3ea74c37c5d359"),
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359"
+        );
+
+        let master = Xpriv::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "",
+        )
+        .unwrap();
+        let wallet = evm_wallet(&master, 0, 0, None).unwrap();
+        assert!(
+            wallet
+                .as_str()
+                .starts_with("0x9858EfFD232B4033E47d90003D41EC34EcaEda94, 0x")
+        );
+    }
+
+    #[test]
+    fn test_keystore() {
+        // scrypt test vector of Web3 Secret Storage: n 262144, r 1 and p 8 of "testpassword",
+        // out of the RFC 7914 range of the scrypt crate, so sealed by its derived key
+        let secret = SecretKey::from_slice(
+            &[
+                0x7a, 0x28, 0xb5, 0xba, 0x57, 0xc5, 0x36, 0x03, 0xb0, 0xb0, 0x7b, 0x56, 0xbb, 0xa7,
+                0x52, 0xf7, 0x78, 0x4b, 0xf5, 0x06, 0xfa, 0x95, 0xed, 0xc3, 0x95, 0xf5, 0xcf, 0x6c,
+                0x75, 0x14, 0xfe, 0x9d,
+            ][..],
+        )
+        .unwrap();
+        let salt = [
+            0xab, 0x0c, 0x78, 0x76, 0x05, 0x26, 0x00, 0xdd, 0x70, 0x35, 0x18, 0xd6, 0xfc, 0x3f,
+            0xe8, 0x98, 0x45, 0x92, 0x14, 0x5b, 0x59, 0x1f, 0xc8, 0xfb, 0x5c, 0x6d, 0x43, 0x19,
+            0x03, 0x34, 0xba, 0x19,
+        ];
+        let iv = [
+            0x83, 0xdb, 0xcc, 0x02, 0xd8, 0xcc, 0xb4, 0x0e, 0x46, 0x61, 0x91, 0xa1, 0x23, 0x79,
+            0x1e, 0x0e,
+        ];
+        let key = [
+            0xfa, 0xc1, 0x92, 0xce, 0xb5, 0xfd, 0x77, 0x29, 0x06, 0xbe, 0xa3, 0xe1, 0x18, 0xa6,
+            0x9e, 0x8b, 0xbb, 0x5c, 0xc2, 0x42, 0x29, 0xe2, 0x0d, 0x87, 0x66, 0xfd, 0x29, 0x82,
+            0x91, 0xbb, 0xa6, 0xbd,
+        ];
+        let (ciphertext, mac) = seal(&secret, &key, &iv);
+        assert_eq!(
+            ciphertext.to_lower_hex_string(),
+            "d172bf743a674da9cdad04534d56926ef8358534d458fffccd4e6ad2fbde479c"
+        );
+        assert_eq!(
+            mac.to_lower_hex_string(),
+            "2103ac29920d71da29f15d75b4a16dbe95cfd7ff8faea1056c33131d846e3097"
+        );
+
+        // the same key and salt at geth's r 8 and p 1
+        let params = scrypt::Params::new(18, 8, 1, 32).unwrap();
+        let json = encrypt(&secret, "testpassword", &params, &salt, &iv, &[0; 16]).unwrap();
+        assert!(json.starts_with(r#"{"address":"008aeeda4d805471df9b2a5b0f38a0c3bcba786b","#));
+        assert!(json.contains(
+            r#""ciphertext":"b160ff7e6d855b53a3f8d65e4b2850584cfaa01751807f19d07c298de16f802d""#
+        ));
+        assert!(json.contains(r#""n":262144,"p":1,"r":8,"#));
+        assert!(json.contains(
+            r#""mac":"daeeba49ffae86381ae33f74c05dc0038888a7806267a04a1ae2183a7f2b7b17""#
+        ));
+        assert!(json.contains(r#""id":"00000000-0000-4000-8000-000000000000""#));
+    }
+}
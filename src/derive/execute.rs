@@ -1,31 +1,26 @@
-use super::{DeriveCommand, arg::MasterKey, multisig::MultiSig};
-use crate::{Execute, utils::inquire_password};
+use super::{DeriveCommand, arg::MasterKey, export::Export, multisig::MultiSig};
+use crate::Execute;
+use crate::utils::{SecretString, SecretXpriv};
 use artimonist::{BIP38, BIP39, Xpriv};
 use std::io::{BufWriter, Write};
 
 impl Execute for DeriveCommand {
     fn execute(&mut self) -> anyhow::Result<()> {
-        let password = match &self.password {
-            Some(p) => p.to_string(),
-            None => inquire_password(self.is_mnemonic())?,
-        };
-
-        match &self.key {
-            MasterKey::Xpriv(master) => {
-                if self.is_multisig() {
-                    self.derive_multisig(master)?
-                } else {
-                    self.derive_wallets(master, &password)?
-                }
-            }
+        let master = match self.key_input.master_key(&mut self.key)?.clone() {
+            MasterKey::Xpriv(master) => master,
             MasterKey::Mnemonic(mnemonic) => {
-                let master = Xpriv::from_mnemonic(&mnemonic.to_string(), &password)?;
-                if self.is_multisig() {
-                    self.derive_multisig(&master)?
-                } else {
-                    self.derive_wallets(&master, &password)?
-                }
+                let passphrase = self.secrets.passphrase()?;
+                SecretXpriv::new(Xpriv::from_mnemonic(&mnemonic, &passphrase)?)
             }
+        };
+
+        if self.is_export() {
+            self.export_keys(&master)?
+        } else if self.is_multisig() {
+            self.derive_multisig(&master)?
+        } else {
+            let password = self.secrets.encryption()?;
+            self.derive_wallets(&master, &password)?
         }
         Ok(())
     }
@@ -42,21 +37,28 @@ impl Wallet for DeriveCommand {
         if self.detail {
             // derive account
             let (xpub, xprv) = self.derive.account(master, self.account)?;
+            let xprv = SecretString::new(xprv);
             let path = self.derive.path(self.account);
 
             // output account
             let mut f = BufWriter::new(std::io::stdout());
             writeln!(f, "account:")?;
             writeln!(f, "[{path}]: {xpub}")?;
-            writeln!(f, "[{path}]: {xprv}")?;
+            writeln!(f, "[{path}]: {}", xprv.as_str())?;
             writeln!(f, "wallets:")?;
         }
 
         // derive wallets
         let mut f = BufWriter::new(std::io::stdout());
         for index in self.index..self.index + self.amount {
-            let (addr, pk) = self.derive.wallet(master, self.account, index)?;
             let path = format!("{}/0/{index}", self.derive.path(self.account));
+            if let Some(chain) = self.derive.chain() {
+                let keys = chain.keys(master, self.account, index, Some(password))?;
+                writeln!(f, "[{path}]: {}", keys.as_str())?;
+                continue;
+            }
+            let (addr, pk) = self.derive.wallet(master, self.account, index)?;
+            let pk = SecretString::new(pk);
             writeln!(f, "[{path}]: {addr}, {}", pk.bip38_encrypt(password)?)?;
         }
         Ok(())
@@ -0,0 +1,120 @@
+use super::{DeriveCommand, arg::ExportFormat, path::KeyChain};
+use crate::utils::{SecretString, inquire_confirm, require_input};
+use anyhow::anyhow;
+use artimonist::Xpriv;
+use std::io::{BufWriter, Write};
+
+type DeriveResult<T = ()> = anyhow::Result<T>;
+
+pub trait Export {
+    fn export_keys(&self, master: &Xpriv) -> DeriveResult;
+
+    fn export_electrum(&self, wallets: &[(String, String, SecretString)]) -> DeriveResult;
+    fn export_core(&self, wallets: &[(String, String, SecretString)]) -> DeriveResult;
+    fn export_wif(&self, wallets: &[(String, String, SecretString)]) -> DeriveResult;
+    fn export_chain(&self, master: &Xpriv, chain: KeyChain) -> DeriveResult;
+}
+
+impl Export for DeriveCommand {
+    fn export_keys(&self, master: &Xpriv) -> DeriveResult {
+        let Some(format) = self.export_keys else {
+            return Err(anyhow!("empty export format"));
+        };
+        // nostr and evm keys have a format of their own, bitcoin wallets one of many
+        let chain = self.derive.chain();
+        match (chain, format) {
+            (Some(_), Some(_)) => {
+                return Err(anyhow!(
+                    "nostr and evm keys are exported by `--export-keys` alone"
+                ));
+            }
+            (None, None) => {
+                return Err(anyhow!(
+                    "bitcoin wallets are exported by `--export-keys <electrum|core|wif>`"
+                ));
+            }
+            _ => {}
+        }
+
+        // unencrypted keys must be confirmed explicitly
+        if !self.yes {
+            require_input("confirmation of unencrypted export", "--yes")?;
+            if !inquire_confirm(
+                "Export unencrypted private keys?",
+                "Anyone who sees the output can spend the funds.",
+            )? {
+                return Err(anyhow!("export cancelled"));
+            }
+        }
+
+        let Some(format) = format else {
+            return self.export_chain(master, chain.expect("checked above"));
+        };
+
+        // derive wallets: (path, address, private key)
+        let wallets = (self.index..self.index + self.amount)
+            .map(|index| {
+                let (addr, pk) = self.derive.wallet(master, self.account, index)?;
+                let path = format!("{}/0/{index}", self.derive.path(self.account));
+                Ok((path, addr, SecretString::new(pk)))
+            })
+            .collect::<DeriveResult<Vec<_>>>()?;
+
+        match format {
+            ExportFormat::Electrum => self.export_electrum(&wallets),
+            ExportFormat::Core => self.export_core(&wallets),
+            ExportFormat::Wif => self.export_wif(&wallets),
+        }
+    }
+
+    fn export_electrum(&self, wallets: &[(String, String, SecretString)]) -> DeriveResult {
+        let mut f = BufWriter::new(std::io::stdout());
+        let script_type = self.derive.script_type();
+        for (_, _, pk) in wallets {
+            writeln!(f, "{script_type}:{}", pk.as_str())?;
+        }
+        Ok(())
+    }
+
+    fn export_core(&self, wallets: &[(String, String, SecretString)]) -> DeriveResult {
+        // timestamp of genesis era makes bitcoin core rescan the whole chain
+        const TIMESTAMP: &str = "1970-01-01T00:00:01Z";
+
+        let mut f = BufWriter::new(std::io::stdout());
+        writeln!(
+            f,
+            "# Wallet dump created by Artimonist {}",
+            env!("CARGO_PKG_VERSION")
+        )?;
+        writeln!(f, "# * Best block at time of backup was unknown.")?;
+        writeln!(f)?;
+        for (path, addr, pk) in wallets {
+            writeln!(
+                f,
+                "{} {TIMESTAMP} label={path} # addr={addr} hdkeypath={path}",
+                pk.as_str()
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "# End of dump")?;
+        Ok(())
+    }
+
+    fn export_wif(&self, wallets: &[(String, String, SecretString)]) -> DeriveResult {
+        let mut f = BufWriter::new(std::io::stdout());
+        for (_, _, pk) in wallets {
+            writeln!(f, "{}", pk.as_str())?;
+        }
+        Ok(())
+    }
+
+    fn export_chain(&self, master: &Xpriv, chain: KeyChain) -> DeriveResult {
+        let mut f = BufWriter::new(std::io::stdout());
+        for index in self.index..self.index + self.amount {
+            let keys = chain.keys(master, self.account, index, None)?;
+            let path = format!("{}/0/{index}", self.derive.path(self.account));
+            writeln!(f, "[{path}]: {}", keys.as_str())?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
 mod arg;
+mod evm;
 mod execute;
+mod export;
 mod multisig;
+mod nostr;
 mod path;
 
-pub use arg::DeriveCommand;
+pub use arg::{DeriveCommand, DerivePath, MasterKey};
@@ -1,4 +1,5 @@
 use crate::DeriveCommand;
+use crate::utils::SecretString;
 use artimonist::Xpriv;
 use std::io::{BufWriter, Write};
 
@@ -7,7 +8,7 @@ type DeriveResult<T = ()> = anyhow::Result<T>;
 pub trait MultiSig {
     fn derive_multisig(&self, master: &Xpriv) -> DeriveResult;
 
-    fn multisig_accounts(&self, accounts: &[(String, String)]) -> DeriveResult;
+    fn multisig_accounts(&self, accounts: &[(String, SecretString)]) -> DeriveResult;
     fn multisig_wallets(&self, wallets: &[(String, String)]);
     fn multisig_scripts(&self, wallets: &[(String, String)]) -> DeriveResult;
 }
@@ -19,8 +20,11 @@ impl MultiSig for DeriveCommand {
 
         // derive accounts
         let accounts = (self.account..self.account + n)
-            .map(|account| self.derive.account(master, account))
-            .collect::<Result<Vec<_>, _>>()?;
+            .map(|account| {
+                let (xpub, xprv) = self.derive.account(master, account)?;
+                Ok((xpub, SecretString::new(xprv)))
+            })
+            .collect::<DeriveResult<Vec<_>>>()?;
 
         // derive wallets
         let wallets = (self.index..self.index + self.amount)
@@ -42,7 +46,7 @@ impl MultiSig for DeriveCommand {
         Ok(())
     }
 
-    fn multisig_accounts(&self, accounts: &[(String, String)]) -> DeriveResult {
+    fn multisig_accounts(&self, accounts: &[(String, SecretString)]) -> DeriveResult {
         let mut f = BufWriter::new(std::io::stdout());
         let path_first = self.derive.path(self.account);
         let path_last = self
@@ -60,7 +64,7 @@ impl MultiSig for DeriveCommand {
             writeln!(f, "Account xprivs: [{path_first}] ~ [{path_last}]")?;
             for (i, (_, xpriv)) in accounts.iter().enumerate() {
                 let path = self.derive.path(self.account + i as u32);
-                writeln!(f, "[{path}]: {xpriv}")?;
+                writeln!(f, "[{path}]: {}", xpriv.as_str())?;
                 // writeln!(f, "  {xpriv}")?;
             }
         }
@@ -0,0 +1,177 @@
+//! Nostr keys of NIP-06, encrypted by NIP-49 as wallets are by BIP38.
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/06.md>
+//! <https://github.com/nostr-protocol/nips/blob/master/49.md>
+
+use crate::utils::SecretString;
+use anyhow::anyhow;
+use artimonist::Xpriv;
+use artimonist::bitcoin::bech32::{self, Bech32, Hrp};
+use artimonist::bitcoin::bip32::DerivationPath;
+use artimonist::bitcoin::hex::DisplayHex;
+use artimonist::bitcoin::secp256k1::{Secp256k1, SecretKey, XOnlyPublicKey};
+use chacha20poly1305::XChaCha20Poly1305;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use std::str::FromStr;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::{Zeroize, Zeroizing};
+
+type DeriveResult<T = ()> = anyhow::Result<T>;
+
+/// Scrypt cost of the encrypted keys: 2^16 rounds, 64 MiB
+const LOG_N: u8 = 16;
+
+/// Key security byte of NIP-49: the client does not track it
+const KEY_SECURITY: u8 = 0x02;
+
+/// Keys of `m/44'/1237'/<account>'/0/<index>` as derive prints them: `npub` and hex public key,
+/// then the secret key encrypted by `password`, or else as `nsec` and hex.
+pub fn nostr_wallet(
+    master: &Xpriv,
+    account: u32,
+    index: u32,
+    password: Option<&str>,
+) -> DeriveResult<SecretString> {
+    let (public, secret) = nostr_keys(master, account, index)?;
+    let npub = npub(&public)?;
+    Ok(SecretString::new(match password {
+        Some(password) => format!("{npub}, {public}, {}", ncryptsec(&secret, password)?),
+        None => {
+            let hex = SecretString::new(secret.secret_bytes().to_lower_hex_string());
+            format!(
+                "{npub}, {public}, {}, {}",
+                nsec(&secret)?.as_str(),
+                hex.as_str()
+            )
+        }
+    }))
+}
+
+/// Key pair of `m/44'/1237'/<account>'/0/<index>`.
+fn nostr_keys(
+    master: &Xpriv,
+    account: u32,
+    index: u32,
+) -> DeriveResult<(XOnlyPublicKey, SecretKey)> {
+    let secp = Secp256k1::signing_only();
+    let path = DerivationPath::from_str(&format!("m/44'/1237'/{account}'/0/{index}"))?;
+    let secret = master.derive_priv(&secp, &path)?.private_key;
+    Ok((secret.x_only_public_key(&secp).0, secret))
+}
+
+fn npub(public: &XOnlyPublicKey) -> DeriveResult<String> {
+    Ok(bech32::encode::<Bech32>(
+        Hrp::parse("npub")?,
+        &public.serialize(),
+    )?)
+}
+
+fn nsec(secret: &SecretKey) -> DeriveResult<SecretString> {
+    let mut bytes = secret.secret_bytes();
+    let nsec = bech32::encode::<Bech32>(Hrp::parse("nsec")?, &bytes);
+    bytes.zeroize();
+    Ok(SecretString::new(nsec?))
+}
+
+/// Secret key encrypted by `password` of random salt and nonce.
+fn ncryptsec(secret: &SecretKey, password: &str) -> DeriveResult<String> {
+    let mut random = [0u8; 40];
+    getrandom::getrandom(&mut random).map_err(|e| anyhow!("{e}"))?;
+    let salt: &[u8; 16] = random[..16].try_into().expect("16 bytes");
+    let nonce: &[u8; 24] = random[16..].try_into().expect("24 bytes");
+    encrypt(secret, password, LOG_N, salt, nonce)
+}
+
+/// `ncryptsec` of version 2: the scrypt cost, salt and nonce, the key security byte,
+/// then the XChaCha20-Poly1305 ciphertext of the secret, the security byte authenticated.
+fn encrypt(
+    secret: &SecretKey,
+    password: &str,
+    log_n: u8,
+    salt: &[u8; 16],
+    nonce: &[u8; 24],
+) -> DeriveResult<String> {
+    let password = SecretString::new(password.nfkc().collect());
+    let mut key = Zeroizing::new([0u8; 32]);
+    let params = scrypt::Params::new(log_n, 8, 1, 32).map_err(|e| anyhow!("{e}"))?;
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut key[..]).map_err(|e| anyhow!("{e}"))?;
+
+    let cipher = XChaCha20Poly1305::new((&*key).into());
+    let plain = Zeroizing::new(secret.secret_bytes());
+    let payload = Payload {
+        msg: &plain[..],
+        aad: &[KEY_SECURITY],
+    };
+    let ciphertext = cipher
+        .encrypt(nonce.into(), payload)
+        .map_err(|e| anyhow!("{e}"))?;
+
+    let mut data = Vec::with_capacity(91);
+    data.extend([0x02, log_n]);
+    data.extend(salt);
+    data.extend(nonce);
+    data.push(KEY_SECURITY);
+    data.extend(ciphertext);
+    Ok(bech32::encode::<Bech32>(Hrp::parse("ncryptsec")?, &data)?)
+}
+
+#[cfg(test)]
+mod nostr_test {
+    use super::*;
+    use artimonist::BIP39;
+
+    #[test]
+    fn test_nip06() {
+        // test vectors of NIP-06
+        let master = |words: &str| Xpriv::from_mnemonic(words, "").unwrap();
+        let root = master(
+            "leader monkey parrot ring guide accident before fence cannon height naive bean",
+        );
+        let (public, secret) = nostr_keys(&root, 0, 0).unwrap();
+        assert_eq!(
+            secret.secret_bytes().to_lower_hex_string(),
+            "7f7ff03d123792d6ac594bfa67bf6d0c0ab55b6b1fdb6249303fe861f1ccba9a"
+        );
+        assert_eq!(
+            public.to_string(),
+            "17162c921dc4d2518f9a101db33695df1afb56ab82f5ff3e5da6eec3ca5cd917"
+        );
+        assert_eq!(
+            nsec(&secret).unwrap().as_str(),
+            "nsec10allq0gjx7fddtzef0ax00mdps9t2kmtrldkyjfs8l5xruwvh2dq0lhhkp"
+        );
+        assert_eq!(
+            npub(&public).unwrap(),
+            "npub1zutzeysacnf9rru6zqwmxd54mud0k44tst6l70ja5mhv8jjumytsd2x7nu"
+        );
+
+        let root = master(
+            "what bleak badge arrange retreat wolf trade produce cricket blur garlic valid proud rude strong choose busy staff weather area salt hollow arm fade",
+        );
+        let (public, secret) = nostr_keys(&root, 0, 0).unwrap();
+        assert_eq!(
+            secret.secret_bytes().to_lower_hex_string(),
+            "c15d739894c81a2fcfd3a2df85a0d2c0dbc47a280d092799f144d73d7ae78add"
+        );
+        assert_eq!(
+            public.to_string(),
+            "d41b22899549e1f3d335a31002cfd382174006e166d3e658e3a5eecdb6463573"
+        );
+    }
+
+    #[test]
+    fn test_ncryptsec() {
+        let secret = SecretKey::from_slice(&[0x35; 32]).unwrap();
+        // decrypted by scrypt and libsodium's XChaCha20-Poly1305 to the secret
+        let encrypted = encrypt(&secret, "nostr", 4, &[1; 16], &[2; 24]).unwrap();
+        assert_eq!(
+            encrypted,
+            "ncryptsec1qgzqzqgpqyqszqgpqyqszqgpqyqszqszqgpqyqszqgpqyqszqgpqyqszqgpqyqszqgpq9qwthqxh753gq45ena3dnalewkyengw9eh63vwfvfmrdrfctu2c6s5rlmk3hlnyg55rqyqrvh654huxq6x0g"
+        );
+        assert!(
+            ncryptsec(&secret, "nostr")
+                .unwrap()
+                .starts_with("ncryptsec1")
+        );
+    }
+}
@@ -1,5 +1,10 @@
 use super::arg::DerivePath;
+use super::{evm::evm_wallet, nostr::nostr_wallet};
+use crate::utils::SecretString;
+use artimonist::bitcoin::bip32::{DerivationPath, Xpub};
+use artimonist::bitcoin::secp256k1::Secp256k1;
 use artimonist::{BIP44, BIP49, BIP84, Xpriv};
+use std::str::FromStr;
 
 enum DeriveMethod {
     Bip44 = 44,
@@ -7,11 +12,53 @@ enum DeriveMethod {
     Bip84 = 84,
 }
 
+/// Chain of the paths of purpose 44 that are no bitcoin wallets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyChain {
+    /// NIP-06: coin type 1237
+    Nostr = 1237,
+    /// Ethereum: coin type 60
+    Evm = 60,
+}
+
 type DeriveResult = anyhow::Result<(String, String)>;
 
 use DeriveMethod::*;
 
+impl KeyChain {
+    /// Keys of an account and index as derive prints them, the secret encrypted by the password
+    #[inline]
+    pub fn keys(
+        &self,
+        master: &Xpriv,
+        account: u32,
+        index: u32,
+        password: Option<&str>,
+    ) -> anyhow::Result<SecretString> {
+        match self {
+            KeyChain::Nostr => nostr_wallet(master, account, index, password),
+            KeyChain::Evm => evm_wallet(master, account, index, password),
+        }
+    }
+}
+
 impl DerivePath {
+    /// Paths of BIP44, BIP49 and BIP84
+    pub fn all() -> [Self; 3] {
+        [
+            (true, false, false),
+            (false, true, false),
+            (false, false, true),
+        ]
+        .map(|(bip44, bip49, bip84)| Self {
+            bip44,
+            bip49,
+            bip84,
+            nostr: false,
+            evm: false,
+        })
+    }
+
     #[inline]
     fn method(&self) -> DeriveMethod {
         match self {
@@ -21,16 +68,44 @@ impl DerivePath {
         }
     }
 
+    /// Chain of the keys, none of bitcoin wallets
+    #[inline]
+    pub fn chain(&self) -> Option<KeyChain> {
+        match self {
+            Self { nostr: true, .. } => Some(KeyChain::Nostr),
+            Self { evm: true, .. } => Some(KeyChain::Evm),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub fn path(&self, account: u32) -> String {
-        match artimonist::NETWORK.is_mainnet() {
-            true => format!("m/{}'/0'/{account}'", self.method() as u8),
-            false => format!("m/{}'/1'/{account}'", self.method() as u8),
+        if let Some(chain) = self.chain() {
+            return format!("m/44'/{}'/{account}'", chain as u32);
+        }
+        match (self.method(), artimonist::NETWORK.is_mainnet()) {
+            (method, true) => format!("m/{}'/0'/{account}'", method as u8),
+            (method, false) => format!("m/{}'/1'/{account}'", method as u8),
+        }
+    }
+
+    /// Electrum script type of the derived wallets
+    #[inline]
+    pub fn script_type(&self) -> &'static str {
+        match self.method() {
+            Bip44 => "p2pkh",
+            Bip49 => "p2wpkh-p2sh",
+            Bip84 => "p2wpkh",
         }
     }
 
     #[inline]
     pub fn account(&self, root: &Xpriv, account: u32) -> DeriveResult {
+        if self.chain().is_some() {
+            let secp = Secp256k1::signing_only();
+            let xprv = root.derive_priv(&secp, &DerivationPath::from_str(&self.path(account))?)?;
+            return Ok((Xpub::from_priv(&secp, &xprv).to_string(), xprv.to_string()));
+        }
         Ok(match self.method() {
             Bip44 => root.bip44_account(account)?,
             Bip49 => root.bip49_account(account)?,
@@ -1,3 +1,5 @@
+use crate::bip85::GENESIS_TIME;
+use crate::utils::{PasswordInput, SecretString};
 use artimonist::{GenericDiagram, Language};
 use clap::builder::TypedValueParser;
 
@@ -19,6 +21,14 @@ pub struct DiagramCommand<T: GenericDiagram> {
     #[clap(short, long)]
     pub file: Option<String>,
 
+    /// Enter the diagram twice to catch typos
+    #[clap(long, conflicts_with = "file")]
+    pub twice: bool,
+
+    /// Save the diagram to a new file in plaintext
+    #[clap(long, value_name = "FILE")]
+    pub save: Option<String>,
+
     /// Export unicode view for non-displayable character
     #[clap(long)]
     pub unicode: bool,
@@ -28,12 +38,20 @@ pub struct DiagramCommand<T: GenericDiagram> {
     pub target: GenerateTarget,
 
     /// Password as salt
-    #[clap(hide = true, long)]
-    pub password: Option<String>,
+    #[clap(hide = true, long, env = "ARTIMONIST_PASSWORD", hide_env_values = true)]
+    pub password: Option<SecretString>,
+
+    /// Password input channels
+    #[clap(flatten)]
+    pub password_input: PasswordInput,
 
     /// Mnemonic language
-    #[clap(hide = true, long)]
+    #[clap(long)]
     pub language: Option<Language>,
+
+    /// Generate from a diagram rated weak
+    #[clap(long)]
+    pub accept_weak: bool,
 }
 
 #[derive(clap::Args, Debug)]
@@ -56,11 +74,170 @@ pub struct GenerateTarget {
     /// Generate password
     #[clap(long)]
     pub pwd: bool,
+
+    /// Password length: 20 to 86 characters, 10 to 80 of base85
+    #[clap(long, value_name = "LENGTH", default_value_t = 20, requires = "pwd")]
+    pub pwd_length: u32,
+
+    /// Password characters
+    #[clap(
+        long,
+        value_name = "TYPE",
+        default_value = "distinct",
+        requires = "pwd"
+    )]
+    pub pwd_type: PwdType,
+
+    /// Generate hex entropy of 16 to 64 bytes, for disk encryption and other keys
+    #[clap(long, value_name = "BYTES", value_parser = clap::value_parser!(u32).range(16..=64))]
+    pub hex: Option<u32>,
+
+    /// Generate 1 to 10000 dice rolls, numbered from 0
+    #[clap(long, value_name = "ROLLS", value_parser = clap::value_parser!(u32).range(1..=10000))]
+    pub dice: Option<u32>,
+
+    /// Sides of the dice, below 2^31
+    #[clap(long, default_value_t = 6, value_parser = clap::value_parser!(u32).range(2..1 << 31), requires = "dice")]
+    pub sides: u32,
+
+    /// Generate OpenSSH ed25519 keys, an Artimonist extension of BIP85
+    #[clap(long)]
+    pub ssh: bool,
+
+    /// Generate OpenPGP keys of ed25519 to sign and cv25519 to encrypt, an Artimonist extension of BIP85
+    #[clap(long, requires_all = ["user_id", "created"])]
+    pub gpg: bool,
+
+    /// User ID of the OpenPGP keys and comment of the SSH keys, like `Alice <alice@example.com>`
+    #[clap(long, value_name = "USER_ID")]
+    pub user_id: Option<String>,
+
+    /// Creation time of the OpenPGP keys as `YYYY-MM-DD` or unix time, part of their fingerprint
+    #[clap(long, value_name = "TIME", value_parser = parse_time, requires = "gpg")]
+    pub created: Option<u32>,
+
+    /// Generate age X25519 identities, an Artimonist extension of BIP85
+    #[clap(long)]
+    pub age: bool,
+
+    /// Generate the seed of a Lightning node: a CLN hsm_secret or an LND aezeed
+    #[clap(long, value_name = "NODE")]
+    pub lightning: Option<LightningNode>,
+
+    /// Birthday of the LND wallet as `YYYY-MM-DD` or unix time, to scan the chain from [default: genesis block]
+    #[clap(long, value_name = "TIME", value_parser = parse_time, requires = "lightning")]
+    pub birthday: Option<u32>,
+
+    /// Passphrase of the LND aezeed, or encrypting the CLN hsm_secret
+    #[clap(
+        long,
+        value_name = "PASSPHRASE",
+        env = "ARTIMONIST_SEED_PASSPHRASE",
+        hide_env_values = true,
+        requires = "lightning"
+    )]
+    pub seed_passphrase: Option<SecretString>,
+
+    /// Write the SSH, OpenPGP and age keys and the hsm_secrets to files in DIR instead of printing them
+    #[clap(long, value_name = "DIR")]
+    pub key_dir: Option<String>,
+}
+
+impl GenerateTarget {
+    /// Check the options of the targets before any input is asked for.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.pwd {
+            self.pwd_type.validate(self.pwd_length)?;
+        }
+        if let Some(birthday) = self.birthday {
+            if self.lightning != Some(LightningNode::Lnd) {
+                anyhow::bail!("only LND seeds have a birthday");
+            }
+            if birthday < GENESIS_TIME {
+                anyhow::bail!("the birthday is before the genesis block");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Implementation of a Lightning node
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum LightningNode {
+    /// Core Lightning, the `hsm_secret` file
+    Cln,
+    /// LND, the aezeed cipher seed
+    Lnd,
+}
+
+/// Unix time of a `YYYY-MM-DD` date at midnight UTC, or of its seconds.
+fn parse_time(s: &str) -> Result<u32, String> {
+    if let Ok(secs) = s.parse::<u32>() {
+        return Ok(secs);
+    }
+    let invalid = || format!("`{s}` is no YYYY-MM-DD date or unix time");
+    let parts: Vec<i64> = s
+        .split('-')
+        .map(|p| p.parse::<i64>().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    let [y, m, d] = parts[..] else {
+        return Err(invalid());
+    };
+    let leap = (y % 4 == 0 && y % 100 != 0) || y % 400 == 0;
+    let month_days = [31, 28 + leap as i64, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    if !(1970..=2105).contains(&y)
+        || !(1..=12).contains(&m)
+        || !(1..=month_days[m as usize - 1]).contains(&d)
+    {
+        return Err(invalid());
+    }
+    // days since 1970-01-01 of the civil calendar
+    let (y, m) = if m <= 2 { (y - 1, m + 9) } else { (y, m - 3) };
+    let days = 365 * y + y / 4 - y / 100 + y / 400 + (153 * m + 2) / 5 + d - 719469;
+    u32::try_from(days * 86400).map_err(|_| invalid())
+}
+
+/// Characters of a generated password
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum PwdType {
+    /// Base58 and `@#$%&*`, the passwords of earlier versions
+    #[default]
+    Distinct,
+    /// Base64 of the BIP85 PWD BASE64 application
+    Base64,
+    /// Base85 of the BIP85 PWD BASE85 application
+    Base85,
 }
 
-impl<T: GenericDiagram> DiagramCommand<T> {
-    #[inline(always)]
-    pub fn has_mnemonic(&self) -> bool {
-        self.target.mnemonic.is_some() || !(self.target.wif || self.target.xprv || self.target.pwd)
+impl PwdType {
+    /// Check a password length is in the range of the application.
+    pub fn validate(&self, length: u32) -> anyhow::Result<()> {
+        let lengths = match self {
+            PwdType::Distinct | PwdType::Base64 => 20..=86,
+            PwdType::Base85 => 10..=80,
+        };
+        if !lengths.contains(&length) {
+            anyhow::bail!(
+                "{self:?} passwords have {} to {} characters",
+                lengths.start(),
+                lengths.end()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod arg_test {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("1970-01-01"), Ok(0));
+        assert_eq!(parse_time("2009-01-03"), Ok(1230940800));
+        assert_eq!(parse_time("2024-02-29"), Ok(1709164800));
+        assert_eq!(parse_time("1231006505"), Ok(1231006505));
+        assert!(parse_time("2023-02-29").is_err());
+        assert!(parse_time("2024-13-01").is_err());
     }
 }
@@ -0,0 +1,361 @@
+//! Full-screen grid editor of diagrams.
+
+use super::format::DiagramCell;
+use super::output::GridTable;
+use crate::utils::{unicode_decode_forms, unicode_describe, unicode_encode};
+use anyhow::anyhow;
+use artimonist::Matrix;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, terminal};
+use std::io::Write;
+use unicode_segmentation::UnicodeSegmentation;
+use zeroize::Zeroize;
+
+const HELP: &str = "Arrows/Tab: move  Enter: edit  Del: clear  Ctrl+Z: undo  Ctrl+S: done  Esc: cancel\n\
+    Type \\u{2248}, U+2248, &asymp;, \\N{ALMOST EQUAL TO} or :key: for other characters";
+
+/// Result of a key press
+#[derive(Debug, PartialEq, Eq)]
+pub enum Action {
+    Continue,
+    Done,
+    Cancel,
+}
+
+/// Editor state of the 7 * 7 cells, as typed with `\u{..}` escapes.
+pub struct GridEditor<T> {
+    title: String,
+    cells: [[String; 7]; 7],
+    cursor: (usize, usize),
+    /// Text of the cell being edited
+    input: Option<String>,
+    /// Asking for confirmation before use
+    confirming: bool,
+    history: Vec<[[String; 7]; 7]>,
+    message: String,
+    phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: DiagramCell> GridEditor<T> {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_owned(),
+            cells: Default::default(),
+            cursor: (0, 0),
+            input: None,
+            confirming: false,
+            history: vec![],
+            message: String::new(),
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Update the state by a key press.
+    pub fn handle(&mut self, key: KeyEvent) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && key.code == KeyCode::Char('c') {
+            return Action::Cancel;
+        }
+        self.message.clear();
+
+        if self.confirming {
+            self.confirming = false;
+            return match key.code {
+                KeyCode::Char('y' | 'Y') => Action::Done,
+                _ => Action::Continue,
+            };
+        }
+
+        if self.input.is_some() {
+            self.edit_key(key.code, ctrl);
+            return Action::Continue;
+        }
+
+        match key.code {
+            KeyCode::Char('z') if ctrl => self.undo(),
+            KeyCode::Char('s') if ctrl => self.finish(),
+            KeyCode::Esc => return Action::Cancel,
+            KeyCode::Enter | KeyCode::F(2) => {
+                let (r, c) = self.cursor;
+                self.input = Some(self.cells[r][c].clone());
+            }
+            KeyCode::Delete | KeyCode::Backspace => self.set_cell(String::new()),
+            KeyCode::Char(ch) if !ctrl => self.input = Some(ch.to_string()),
+            KeyCode::Tab => self.move_next(),
+            code => self.navigate(code),
+        }
+        Action::Continue
+    }
+
+    /// Key press while editing a cell.
+    fn edit_key(&mut self, code: KeyCode, ctrl: bool) {
+        match code {
+            // invalid text stays in editing
+            KeyCode::Enter | KeyCode::Tab if self.commit() => self.move_next(),
+            KeyCode::Up | KeyCode::Down if self.commit() => self.navigate(code),
+            KeyCode::Esc => self.input = None,
+            KeyCode::Backspace => {
+                // remove a whole character, not the last code point of an emoji
+                if let Some(input) = &mut self.input {
+                    let len = input
+                        .grapheme_indices(true)
+                        .next_back()
+                        .map_or(0, |(i, _)| i);
+                    input.truncate(len);
+                }
+            }
+            KeyCode::Char(ch) if !ctrl => {
+                if let Some(input) = &mut self.input {
+                    input.push(ch);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Check all cells and ask for confirmation.
+    fn finish(&mut self) {
+        match self.matrix() {
+            Ok(_) => self.confirming = true,
+            Err((pos, msg)) => {
+                self.cursor = pos;
+                self.message = msg;
+            }
+        }
+    }
+
+    /// Store the edited text into the cell, return false if it is invalid.
+    fn commit(&mut self) -> bool {
+        let Some(input) = self.input.take() else {
+            return true;
+        };
+        let decoded = unicode_decode_forms(&input);
+        if let (false, Err(msg)) = (decoded.is_empty(), T::from_input(&decoded)) {
+            self.message = msg;
+            self.input = Some(input);
+            return false;
+        }
+        self.set_cell(input);
+        true
+    }
+
+    fn set_cell(&mut self, text: String) {
+        let (r, c) = self.cursor;
+        if self.cells[r][c] != text {
+            self.history.push(self.cells.clone());
+            self.cells[r][c] = text;
+        }
+    }
+
+    fn undo(&mut self) {
+        match self.history.pop() {
+            Some(cells) => self.cells = cells,
+            None => self.message = "nothing to undo".into(),
+        }
+    }
+
+    fn move_next(&mut self) {
+        let n = (self.cursor.0 * 7 + self.cursor.1 + 1) % 49;
+        self.cursor = (n / 7, n % 7);
+    }
+
+    fn navigate(&mut self, code: KeyCode) {
+        let (r, c) = &mut self.cursor;
+        match code {
+            KeyCode::Up => *r = (*r + 6) % 7,
+            KeyCode::Down => *r = (*r + 1) % 7,
+            KeyCode::Left => *c = (*c + 6) % 7,
+            KeyCode::Right => *c = (*c + 1) % 7,
+            KeyCode::Home => *c = 0,
+            KeyCode::End => *c = 6,
+            _ => {}
+        }
+    }
+
+    /// Decoded diagram, or the first invalid cell with its error.
+    pub fn matrix(&self) -> Result<Matrix<T, 7, 7>, ((usize, usize), String)> {
+        let mut mx: Matrix<T, 7, 7> = std::array::from_fn(|_| std::array::from_fn(|_| None));
+        for (r, row) in self.cells.iter().enumerate() {
+            for (c, text) in row.iter().enumerate() {
+                let decoded = unicode_decode_forms(text);
+                if !decoded.is_empty() {
+                    let cell = T::from_input(&decoded)
+                        .map_err(|msg| ((r, c), format!("cell {},{}: {msg}", r + 1, c + 1)))?;
+                    mx[r][c] = Some(cell);
+                }
+            }
+        }
+        Ok(mx)
+    }
+
+    /// Screen content: the diagram preview with the cursor cell in brackets,
+    /// the text of the cell and its unicode view.
+    pub fn render(&self) -> String {
+        let (r, c) = self.cursor;
+        let preview: Matrix<String, 7, 7> = std::array::from_fn(|i| {
+            std::array::from_fn(|j| {
+                let text = match (&self.input, (i, j) == (r, c)) {
+                    (Some(input), true) => input,
+                    _ => &self.cells[i][j],
+                };
+                let decoded = unicode_decode_forms(text);
+                match (i, j) == (r, c) {
+                    true => Some(format!("[{decoded}]")),
+                    false => Some(decoded),
+                }
+            })
+        });
+
+        let text = self.input.as_ref().unwrap_or(&self.cells[r][c]);
+        let editing = if self.input.is_some() { "_" } else { "" };
+        let mut screen = format!("{}\n{}\n", self.title, preview.fmt_table(false));
+        screen += &format!("Cell {},{}: {text}{editing}\n", r + 1, c + 1);
+        let decoded = unicode_decode_forms(text);
+        screen += &format!("Unicode: {}\n", unicode_encode(&decoded));
+        if !decoded.is_ascii() {
+            screen += &format!("Characters: {}\n", unicode_describe(&decoded));
+        }
+        screen += &match self.confirming {
+            true => "Use this diagram? [y/N]".to_owned(),
+            false => format!("{}\n{HELP}", self.message),
+        };
+        screen
+    }
+}
+
+impl<T> Drop for GridEditor<T> {
+    fn drop(&mut self) {
+        self.cells.iter_mut().flatten().for_each(Zeroize::zeroize);
+        self.history
+            .iter_mut()
+            .flatten()
+            .flatten()
+            .for_each(Zeroize::zeroize);
+        self.input.zeroize();
+    }
+}
+
+/// Raw mode on the alternate screen, restored on drop.
+struct RawScreen;
+
+impl RawScreen {
+    fn enter() -> std::io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(
+            std::io::stdout(),
+            terminal::EnterAlternateScreen,
+            cursor::Hide
+        )?;
+        Ok(Self)
+    }
+
+    fn draw(&self, screen: &str) -> std::io::Result<()> {
+        let mut out = std::io::stdout();
+        execute!(
+            out,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+        write!(out, "{}", screen.replace('\n', "\r\n"))?;
+        out.flush()
+    }
+}
+
+impl Drop for RawScreen {
+    fn drop(&mut self) {
+        let _ = execute!(
+            std::io::stdout(),
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Edit a diagram in the terminal until the user confirms or cancels it.
+pub fn edit<T: DiagramCell>(title: &str) -> anyhow::Result<Matrix<T, 7, 7>> {
+    let mut editor = GridEditor::<T>::new(title);
+    let screen = RawScreen::enter()?;
+    loop {
+        screen.draw(&editor.render())?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match editor.handle(key) {
+            Action::Continue => {}
+            Action::Cancel => return Err(anyhow!("diagram input cancelled")),
+            Action::Done => return editor.matrix().map_err(|(_, msg)| anyhow!(msg)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod editor_test {
+    use super::*;
+
+    fn press(editor: &mut GridEditor<impl DiagramCell>, keys: &[KeyCode]) -> Action {
+        let mut action = Action::Continue;
+        for &code in keys {
+            action = editor.handle(KeyEvent::new(code, KeyModifiers::NONE));
+        }
+        action
+    }
+
+    fn ctrl(ch: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(ch), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn test_editor() {
+        use KeyCode::*;
+
+        let mut editor = GridEditor::<String>::new("complex");
+        press(&mut editor, &[Char('B'), Char('T'), Char('C'), Enter]);
+        press(&mut editor, &[Down, Char('\\'), Char('u'), Char('{')]);
+        press(
+            &mut editor,
+            &[Char('2'), Char('0'), Char('b'), Char('f'), Char('}')],
+        );
+        assert!(editor.render().contains("Unicode: \\u{20bf}"));
+        press(&mut editor, &[Enter, Up, Left, Left, Delete]);
+        assert!(editor.matrix().unwrap()[0][0].is_none());
+        assert_eq!(editor.handle(ctrl('z')), Action::Continue);
+
+        assert_eq!(editor.handle(ctrl('s')), Action::Continue);
+        assert!(editor.render().contains("Use this diagram?"));
+        assert_eq!(press(&mut editor, &[Char('y')]), Action::Done);
+
+        let mx = editor.matrix().unwrap();
+        assert_eq!(mx[0][0].as_deref(), Some("BTC"));
+        assert_eq!(mx[1][1].as_deref(), Some("₿"));
+        assert_eq!(mx.iter().flatten().flatten().count(), 2);
+    }
+
+    #[test]
+    fn test_editor_invalid() {
+        use KeyCode::*;
+
+        // a simple cell holds one character
+        let mut editor = GridEditor::<char>::new("simple");
+        press(&mut editor, &[Char('a'), Char('b'), Enter]);
+        assert!(editor.render().contains("one character"));
+        press(&mut editor, &[Backspace, Enter, Esc]);
+        assert_eq!(editor.matrix().unwrap()[0][0], Some('a'));
+
+        // backspace removes a whole emoji, which a simple cell cannot hold
+        press(
+            &mut editor,
+            &[Enter, Backspace, Char('👍'), Char('🏽'), Enter],
+        );
+        assert!(editor.render().contains("would be truncated"));
+        press(&mut editor, &[Backspace, Char('b'), Enter]);
+        assert_eq!(editor.matrix().unwrap()[0][1], Some('b'));
+
+        // editing is cancelled, the diagram is not
+        assert_eq!(press(&mut editor, &[Esc]), Action::Cancel);
+        assert_eq!(editor.handle(ctrl('c')), Action::Cancel);
+    }
+}
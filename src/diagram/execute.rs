@@ -1,51 +1,114 @@
-use super::{DiagramCommand, matrix::LoadMatrix, output::ConsoleOutput};
-use crate::utils::{inquire_password, select_language};
-use artimonist::{ComplexDiagram, Language, Matrix, SimpleDiagram};
+use super::{DiagramCommand, matrix::LoadMatrix, output::ConsoleOutput, output::DeriveTargets};
+use super::{format::DiagramCell, strength::Strength};
+use crate::utils::{
+    SecretInput, inquire_password, require_input, select_language, unicode_encode, unicode_warnings,
+};
+use anyhow::anyhow;
+use artimonist::{ComplexDiagram, GenericDiagram, Language, Matrix, SimpleDiagram};
+use zeroize::Zeroize;
 
 impl crate::Execute for DiagramCommand<SimpleDiagram> {
     fn execute(&mut self) -> anyhow::Result<()> {
-        // load matrix data from file or inquire it from user
-        let mx = match &self.file {
-            Some(file) => Matrix::<char>::from_file(file)?,
-            None => Matrix::<char>::from_inquire()?,
-        };
-
-        // choose a mnemonic language if needed
-        if self.has_mnemonic() && self.language.is_none() {
-            self.language = Some(select_language(&Language::all())?);
-        }
-
-        // inquire the encryption password as salt
-        if self.password.is_none() {
-            self.password = Some(inquire_password(true)?);
-        }
-
-        // output the diagram's result
-        SimpleDiagram(mx).display(self)?;
-        Ok(())
+        // output the diagram's result, then wipe the cells
+        let mut diagram = SimpleDiagram(self.prepare()?);
+        let result = diagram.display(self);
+        diagram.0.zeroize();
+        result
     }
 }
 
 impl crate::Execute for DiagramCommand<ComplexDiagram> {
     fn execute(&mut self) -> anyhow::Result<()> {
+        // output the diagram's result, then wipe the cells
+        let mut diagram = ComplexDiagram(self.prepare()?);
+        let result = diagram.display(self);
+        diagram.0.zeroize();
+        result
+    }
+}
+
+impl<D: GenericDiagram> DiagramCommand<D> {
+    /// Load and rate the matrix, save it once accepted, then settle the language and password.
+    fn prepare<T>(&mut self) -> anyhow::Result<Matrix<T, 7, 7>>
+    where
+        T: DiagramCell + PartialEq + Zeroize,
+    {
+        self.target.validate()?;
+
         // load the matrix from file or inquire it from user
         let mx = match &self.file {
-            Some(file) => Matrix::<String>::from_file(file)?,
-            None => Matrix::<String>::from_inquire()?,
+            Some(file) => Matrix::<T, 7, 7>::from_file(file)?,
+            None => {
+                require_input("diagram", "--file")?;
+                Matrix::<T, 7, 7>::from_inquire(self.twice)?
+            }
         };
 
+        // warn about lookalike characters, rate the diagram before asking for more input
+        check_characters(&mx);
+        self.check_strength(&mx)?;
+
+        // save the accepted diagram for later runs
+        if let Some(path) = &self.save {
+            mx.to_file(path)?;
+            eprintln!(
+                "Warning: diagram is saved in plaintext to `{path}`, anyone with it and the password can restore the wallets."
+            );
+        }
+
         // choose a mnemonic language if needed
         if self.has_mnemonic() && self.language.is_none() {
-            self.language = Some(select_language(&Language::all())?);
+            require_input("mnemonic language", "--language")?;
+            self.language = Some(select_language(Language::all())?);
         }
 
-        // inquire the encryption password as salt
-        if self.password.is_none() {
-            self.password = Some(inquire_password(true)?);
-        }
+        // read or inquire the encryption password as salt
+        let password = self.password.as_ref();
+        self.password = Some(
+            self.password_input
+                .resolve(password, || inquire_password(true))?,
+        );
+        Ok(mx)
+    }
 
-        // output the diagram's result
-        ComplexDiagram(mx).display(self)?;
+    /// Print the strength rating, refuse weak diagrams unless accepted.
+    fn check_strength<T: DiagramCell>(&self, mx: &Matrix<T, 7, 7>) -> anyhow::Result<()> {
+        let strength = Strength::estimate(mx);
+        for warning in &strength.warnings {
+            eprintln!("Warning: {warning}");
+        }
+        eprintln!(
+            "Strength: {} (about {:.0} bits)",
+            strength.rating(),
+            strength.bits
+        );
+        if strength.is_weak() && !self.accept_weak {
+            return Err(anyhow!(
+                "diagram is too weak to protect a wallet, fill more cells or use --accept-weak"
+            ));
+        }
         Ok(())
     }
 }
+
+/// Warn about cells holding invisible, mixed script or lookalike characters,
+/// with their unicode view to tell them apart.
+fn check_characters<T: DiagramCell>(mx: &Matrix<T, 7, 7>) {
+    for (r, row) in mx.iter().enumerate() {
+        for (c, cell) in row.iter().enumerate() {
+            let Some(text) = cell.as_ref().map(DiagramCell::to_text) else {
+                continue;
+            };
+            let warnings = unicode_warnings(&text);
+            if !warnings.is_empty() {
+                eprintln!(
+                    "Warning: cell {},{} `{}` {}",
+                    r + 1,
+                    c + 1,
+                    unicode_encode(&text),
+                    warnings.join(", ")
+                );
+            }
+        }
+    }
+}
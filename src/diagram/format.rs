@@ -0,0 +1,495 @@
+//! Diagram file format.
+//!
+//! Version 1 records the diagram type, an optional checksum and sparse cells:
+//! ```text
+//! # comment
+//! format = 1
+//! type = complex
+//! checksum = 6a3c2f10
+//! 1,4 = "BTC"
+//! 3,3 = "Satoshi"
+//! ```
+//! Rows and columns count from 1, values may contain `\u{..}` escapes and the other
+//! character forms of `unicode_decode_forms`.
+//!
+//! Legacy files hold seven rows of seven quoted cells: `""  "BTC"  ""  ""  ""  ""  ""`.
+//! Their cells decode `\u{..}` escapes only, as they always did.
+
+use crate::utils::{
+    SecretString, starts_with_form, unicode_decode, unicode_decode_forms, unicode_encode,
+};
+use artimonist::Matrix;
+use artimonist::bitcoin::hashes::{Hash, sha256};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use zeroize::Zeroizing;
+
+/// Latest version of the diagram file format
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Max characters of a complex diagram cell
+const COMPLEX_CELL_MAX: usize = 20;
+
+/// Diagram file error at a position, counted in characters from 1.
+#[derive(thiserror::Error, Debug)]
+#[error("line {line}, column {col}: {msg}")]
+pub struct FormatError {
+    pub line: usize,
+    pub col: usize,
+    pub msg: String,
+}
+
+/// Cell of a diagram file
+pub trait DiagramCell: Sized {
+    /// Diagram type recorded in files
+    const KIND: &'static str;
+
+    /// Parse a decoded, non-empty cell as it is.
+    fn from_text(s: &str) -> Result<Self, String>;
+
+    /// Parse a decoded, non-empty cell of the editor or a file of format 1 into NFC, so the
+    /// same visible text gives the same diagram whatever form it was typed in. Legacy files
+    /// keep their cells as typed, the wallets they always gave.
+    fn from_input(s: &str) -> Result<Self, String> {
+        Self::from_text(&Zeroizing::new(s.nfc().collect::<String>()))
+    }
+
+    /// Text of the cell for checksum and output.
+    fn to_text(&self) -> String;
+}
+
+impl DiagramCell for char {
+    const KIND: &'static str = "simple";
+
+    /// A simple cell holds one code point, a longer grapheme such as an emoji
+    /// with skin tone, a flag or a ZWJ sequence is refused instead of truncated.
+    fn from_text(s: &str) -> Result<Self, String> {
+        let mut graphemes = s.graphemes(true);
+        let (Some(grapheme), None) = (graphemes.next(), graphemes.next()) else {
+            return Err(format!(
+                "simple cell must be one character, found {}",
+                s.graphemes(true).count()
+            ));
+        };
+        let mut chars = grapheme.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => Ok(ch),
+            _ => Err(format!(
+                "simple cell is a character of {} code points and would be truncated, use a complex diagram",
+                grapheme.chars().count()
+            )),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl DiagramCell for String {
+    const KIND: &'static str = "complex";
+
+    fn from_text(s: &str) -> Result<Self, String> {
+        match s.chars().count() {
+            n if n > COMPLEX_CELL_MAX => Err(format!(
+                "complex cell has {n} characters, at most {COMPLEX_CELL_MAX} are allowed"
+            )),
+            _ => Ok(s.to_owned()),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        self.clone()
+    }
+}
+
+/// Parse a diagram file of version 1 or the legacy format.
+pub fn parse<T: DiagramCell>(text: &str) -> Result<Matrix<T, 7, 7>, FormatError> {
+    let first = text
+        .lines()
+        .map(str::trim)
+        .find(|ln| !ln.is_empty() && !ln.starts_with('#'));
+    match first {
+        Some(ln) if ln.starts_with('"') => parse_legacy(text),
+        Some(ln) if ln.starts_with("format") => parse_v1(text),
+        _ => Err(FormatError {
+            line: 1,
+            col: 1,
+            msg: "unknown diagram file, expected `format = 1` or rows of quoted cells".into(),
+        }),
+    }
+}
+
+/// Checksum of the diagram type and cells: first 4 bytes of sha256 in hex.
+pub fn checksum<T: DiagramCell>(mx: &Matrix<T, 7, 7>) -> String {
+    let mut canonical = Zeroizing::new(format!("{}\n", T::KIND));
+    for (r, row) in mx.iter().enumerate() {
+        for (c, cell) in row.iter().enumerate() {
+            if let Some(v) = cell {
+                let text = Zeroizing::new(v.to_text());
+                canonical.push_str(&format!("{},{}={}\n", r + 1, c + 1, text.as_str()));
+            }
+        }
+    }
+    let hash = sha256::Hash::hash(canonical.as_bytes()).to_byte_array();
+    hash[..4].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// First cell which is not in NFC, as `row,col`: a file of format 1 would read it composed.
+pub fn unnormalized<T: DiagramCell>(mx: &Matrix<T, 7, 7>) -> Option<String> {
+    (0..49).map(|i| (i / 7, i % 7)).find_map(|(r, c)| {
+        let text = Zeroizing::new(mx[r][c].as_ref()?.to_text());
+        match text.nfc().eq(text.chars()) {
+            true => None,
+            false => Some(format!("{},{}", r + 1, c + 1)),
+        }
+    })
+}
+
+/// Text of a diagram in the latest format, with non-ascii characters, quotes
+/// and backslashes escaped as `\u{..}`.
+pub fn to_text<T: DiagramCell>(mx: &Matrix<T, 7, 7>) -> SecretString {
+    use std::fmt::Write;
+    // 49 cells of 20 characters escaped in 10 bytes at most, reserved to avoid reallocation
+    const CAPACITY: usize = 16 * 1024;
+
+    let mut out = String::with_capacity(CAPACITY);
+    let _ = writeln!(out, "# Artimonist diagram");
+    let _ = writeln!(out, "format = {FORMAT_VERSION}");
+    let _ = writeln!(out, "type = {}", T::KIND);
+    let _ = writeln!(out, "checksum = {}", checksum(mx));
+    for (r, row) in mx.iter().enumerate() {
+        for (c, cell) in row.iter().enumerate() {
+            if let Some(v) = cell {
+                let escaped = Zeroizing::new(escape(&v.to_text()));
+                let _ = writeln!(out, "{},{} = \"{}\"", r + 1, c + 1, escaped.as_str());
+            }
+        }
+    }
+    SecretString::new(out)
+}
+
+/// Escape a cell to read back as it is: quotes, backslashes and the start of
+/// any character form like `U+2248` or `:key:` are written as `\u{..}`.
+fn escape(s: &str) -> String {
+    s.char_indices()
+        .map(|(i, c)| match c {
+            '"' | '\\' => format!("\\u{{{:x}}}", c as u32),
+            _ if starts_with_form(&s[i..]) => format!("\\u{{{:x}}}", c as u32),
+            _ => unicode_encode(c.encode_utf8(&mut [0; 4])),
+        })
+        .collect()
+}
+
+fn empty_matrix<T>() -> Matrix<T, 7, 7> {
+    std::array::from_fn(|_| std::array::from_fn(|_| None))
+}
+
+fn parse_legacy<T: DiagramCell>(text: &str) -> Result<Matrix<T, 7, 7>, FormatError> {
+    let mut mx = empty_matrix();
+    let mut rows = 0;
+    for (i, line) in text.lines().enumerate() {
+        let lno = i + 1;
+        if rows == 7 {
+            if !line.trim().is_empty() {
+                eprintln!("Warning: line {lno}: ignoring text after the 7 rows of the diagram");
+                break;
+            }
+            continue;
+        }
+
+        let chars = Zeroizing::new(line.chars().collect::<Vec<_>>());
+        let mut pos = skip_spaces(&chars, 0);
+        let mut col = 0;
+        while pos < chars.len() {
+            if col == 7 {
+                return Err(error(lno, pos, "extra cell, a row has 7 cells"));
+            }
+            let (raw, end) = quoted(&chars, pos, lno)?;
+            if end < chars.len() && !chars[end].is_whitespace() {
+                return Err(error(lno, end, "expected spaces between cells"));
+            }
+            let decoded = Zeroizing::new(unicode_decode(&raw));
+            if !decoded.is_empty() {
+                mx[rows][col] = Some(legacy_cell::<T>(&decoded, lno, pos)?);
+            }
+            col += 1;
+            pos = skip_spaces(&chars, end);
+        }
+        if col != 7 {
+            return Err(error(
+                lno,
+                chars.len(),
+                &format!("row has {col} cells, expected 7"),
+            ));
+        }
+        rows += 1;
+    }
+    if rows != 7 {
+        return Err(error(
+            text.lines().count() + 1,
+            0,
+            &format!("diagram has {rows} rows, expected 7"),
+        ));
+    }
+    Ok(mx)
+}
+
+/// Legacy simple diagrams keep the first character of longer cells.
+fn legacy_cell<T: DiagramCell>(s: &str, lno: usize, pos: usize) -> Result<T, FormatError> {
+    let mut graphemes = s.graphemes(true);
+    let first = graphemes.next().unwrap_or_default();
+    if T::KIND == char::KIND && graphemes.next().is_some() {
+        eprintln!(
+            "Warning: line {lno}, column {}: only the first character of the cell is used",
+            pos + 1
+        );
+        return T::from_text(first).map_err(|msg| error(lno, pos, &msg));
+    }
+    T::from_text(s).map_err(|msg| error(lno, pos, &msg))
+}
+
+fn parse_v1<T: DiagramCell>(text: &str) -> Result<Matrix<T, 7, 7>, FormatError> {
+    let mut mx = empty_matrix();
+    let mut version = None;
+    let mut kind = None;
+    let mut expected = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let lno = i + 1;
+        let chars = Zeroizing::new(line.chars().collect::<Vec<_>>());
+        let pos = skip_spaces(&chars, 0);
+        if pos == chars.len() || chars[pos] == '#' {
+            continue;
+        }
+
+        // cell entry: row,col = "value"
+        if chars[pos].is_ascii_digit() {
+            if version.is_none() {
+                return Err(error(lno, pos, "`format` must come before cells"));
+            }
+            let (row, pos) = index(&chars, pos, lno)?;
+            let pos = expect(&chars, pos, ',', lno)?;
+            let (col, pos) = index(&chars, pos, lno)?;
+            let pos = expect(&chars, pos, '=', lno)?;
+            let start = skip_spaces(&chars, pos);
+            let (raw, end) = quoted(&chars, start, lno)?;
+            let end = skip_spaces(&chars, end);
+            if end < chars.len() {
+                return Err(error(lno, end, "unexpected text after the cell"));
+            }
+
+            let decoded = Zeroizing::new(unicode_decode_forms(&raw));
+            if decoded.is_empty() {
+                return Err(error(lno, start, "empty cell, omit the entry instead"));
+            }
+            let cell = &mut mx[row - 1][col - 1];
+            if cell.is_some() {
+                return Err(error(lno, 0, &format!("cell {row},{col} is set twice")));
+            }
+            *cell = Some(T::from_input(&decoded).map_err(|msg| error(lno, start, &msg))?);
+            continue;
+        }
+
+        // header entry: key = value
+        let line = line.trim();
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(error(
+                lno,
+                pos,
+                "expected `key = value` or `row,col = \"value\"`",
+            ));
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let slot = match key {
+            "format" => &mut version,
+            "type" => &mut kind,
+            "checksum" => &mut expected,
+            _ => return Err(error(lno, pos, &format!("unknown key `{key}`"))),
+        };
+        if slot.replace((value.to_owned(), lno)).is_some() {
+            return Err(error(lno, pos, &format!("`{key}` is set twice")));
+        }
+        if key != "format" && version.is_none() {
+            return Err(error(lno, pos, "`format` must be the first entry"));
+        }
+    }
+
+    match version {
+        Some((v, _)) if v == FORMAT_VERSION.to_string() => {}
+        Some((v, lno)) => {
+            return Err(error(lno, 0, &format!("unsupported format version `{v}`")));
+        }
+        None => return Err(error(1, 0, "missing `format` entry")),
+    }
+    match kind {
+        Some((k, _)) if k == T::KIND => {}
+        Some((k, lno)) if k == char::KIND || k == String::KIND => {
+            return Err(error(
+                lno,
+                0,
+                &format!("diagram is {k}, use the `{k}` command to load it"),
+            ));
+        }
+        Some((k, lno)) => return Err(error(lno, 0, &format!("unknown diagram type `{k}`"))),
+        None => return Err(error(1, 0, "missing `type` entry")),
+    }
+    if let Some((sum, lno)) = expected {
+        let actual = checksum(&mx);
+        if !sum.eq_ignore_ascii_case(&actual) {
+            return Err(error(
+                lno,
+                0,
+                &format!("checksum mismatch, file has {sum} but cells give {actual}"),
+            ));
+        }
+    }
+    Ok(mx)
+}
+
+fn error(line: usize, pos: usize, msg: &str) -> FormatError {
+    FormatError {
+        line,
+        col: pos + 1,
+        msg: msg.to_owned(),
+    }
+}
+
+fn skip_spaces(chars: &[char], mut pos: usize) -> usize {
+    while pos < chars.len() && chars[pos].is_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+/// Quoted value starting at `pos`, return the raw text and the position after it.
+fn quoted(chars: &[char], pos: usize, lno: usize) -> Result<(String, usize), FormatError> {
+    if chars.get(pos) != Some(&'"') {
+        return Err(error(lno, pos, "expected a quoted cell"));
+    }
+    match chars[pos + 1..].iter().position(|&c| c == '"') {
+        Some(len) => {
+            let raw = chars[pos + 1..pos + 1 + len].iter().collect();
+            Ok((raw, pos + len + 2))
+        }
+        None => Err(error(lno, pos, "unterminated quote")),
+    }
+}
+
+/// Row or column index from 1 to 7.
+fn index(chars: &[char], pos: usize, lno: usize) -> Result<(usize, usize), FormatError> {
+    let pos = skip_spaces(chars, pos);
+    let len = chars[pos..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit())
+        .count();
+    let digits: String = chars[pos..pos + len].iter().collect();
+    match digits.parse::<usize>() {
+        Ok(n @ 1..=7) => Ok((n, pos + len)),
+        _ => Err(error(lno, pos, "row and column must be from 1 to 7")),
+    }
+}
+
+fn expect(chars: &[char], pos: usize, ch: char, lno: usize) -> Result<usize, FormatError> {
+    let pos = skip_spaces(chars, pos);
+    match chars.get(pos) {
+        Some(&c) if c == ch => Ok(pos + 1),
+        _ => Err(error(lno, pos, &format!("expected `{ch}`"))),
+    }
+}
+
+#[cfg(test)]
+mod format_test {
+    use super::*;
+
+    const LEGACY: &str = r#"""  ""  ""  "BTC"  ""  ""  ""
+""  ""  ""  ""  ""  ""  ""
+""  ""  "Satoshi"  ""  "\u{20bf}"  ""  ""
+""  ""  ""  ""  ""  ""  ""
+""  ""  ""  ""  ""  ""  ""
+""  ""  ""  ""  ""  ""  ""
+""  ""  ""  ""  ""  ""  ""
+"#;
+
+    #[test]
+    fn test_parse_v1() {
+        let legacy = parse::<String>(LEGACY).unwrap();
+        let sum = checksum(&legacy);
+        let v1 = format!(
+            "# my diagram\nformat = 1\ntype = complex\nchecksum = {sum}\n1,4 = \"BTC\"\n3,3 = \"Satoshi\"\n3, 5 = \"\\u{{20bf}}\"\n"
+        );
+        assert_eq!(parse::<String>(&v1).unwrap(), legacy);
+
+        // cells are delimited by quotes, not by the amount of spaces
+        let spaced = LEGACY.replacen(r#""BTC"  """#, r#""BTC" """#, 1);
+        assert_eq!(parse::<String>(&spaced).unwrap(), legacy);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            // legacy rows
+            (LEGACY.replacen(r#""BTC"  """#, r#""BTC"""#, 1), 1, 18),
+            (LEGACY.replacen("\"\"\n", "\"\"  \"\"\n", 1), 1, 32),
+            (LEGACY.replacen("\"\"  \"\"\n", "\"\"\n", 1), 1, 26),
+            (LEGACY.replacen("Satoshi", &"x".repeat(21), 1), 3, 9),
+            (LEGACY.replacen("\"BTC", "BTC", 1), 1, 13),
+            // version 1
+            ("format = 1\ntype = complex\n1,8 = \"x\"".into(), 3, 3),
+            ("format = 1\ntype = complex\n1,1 = \"\"".into(), 3, 7),
+            (
+                "format = 1\ntype = complex\n1,1 = \"x\"\n1,1 = \"y\"".into(),
+                4,
+                1,
+            ),
+            ("format = 1\ntype = simple\n1,1 = \"x\"".into(), 2, 1),
+            ("format = 2\ntype = complex".into(), 1, 1),
+            (
+                "format = 1\ntype = complex\nchecksum = 00000000".into(),
+                3,
+                1,
+            ),
+            ("format = 1\ncolor = red".into(), 2, 1),
+        ];
+        for (text, line, col) in cases {
+            let e = parse::<String>(&text).unwrap_err();
+            assert_eq!((e.line, e.col), (line, col), "{e}");
+        }
+    }
+    #[test]
+    fn test_escape_forms() {
+        // text which looks like a character form is kept literally
+        let mut mx: Matrix<String, 7, 7> = std::array::from_fn(|_| std::array::from_fn(|_| None));
+        mx[0][0] = Some("U+2248 &amp; :key:".to_owned());
+        mx[3][3] = Some(r"\N{BITCOIN SIGN}".to_owned());
+        mx[6][6] = Some("18:15:05 \"≈\"".to_owned());
+        assert_eq!(parse::<String>(&to_text(&mx)).unwrap(), mx);
+    }
+
+    #[test]
+    fn test_simple_grapheme() {
+        assert_eq!(char::from_text("\u{20bf}"), Ok('\u{20bf}'));
+        // emoji with skin tone, flag, ZWJ sequence and combining mark
+        for s in ["👍🏽", "🇯🇵", "👨\u{200d}👩\u{200d}👧", "x\u{301}"] {
+            let e = char::from_text(s).unwrap_err();
+            assert!(e.contains("would be truncated"), "{e}");
+        }
+        assert!(char::from_text("ab").unwrap_err().contains("found 2"));
+        // decomposed input is composed first, legacy cells are kept as typed
+        assert_eq!(char::from_input("e\u{301}"), Ok('\u{e9}'));
+        assert_eq!(String::from_input("e\u{301}"), Ok("\u{e9}".to_owned()));
+        assert_eq!(String::from_text("e\u{301}"), Ok("e\u{301}".to_owned()));
+        let legacy = LEGACY.replacen("Satoshi", "Jose\u{301}", 1);
+        let mx = parse::<String>(&legacy).unwrap();
+        assert_eq!(mx[2][2].as_deref(), Some("Jose\u{301}"));
+        assert_eq!(unnormalized(&mx).as_deref(), Some("3,3"));
+        assert_eq!(unnormalized(&parse::<String>(LEGACY).unwrap()), None);
+
+        // legacy cells keep whole first characters only
+        let legacy = LEGACY.replacen("\"BTC\"", "\"BT\"", 1);
+        assert_eq!(parse::<char>(&legacy).unwrap()[0][3], Some('B'));
+        let legacy = LEGACY.replacen("\"BTC\"", "\"👍🏽\"", 1);
+        let e = parse::<char>(&legacy).unwrap_err();
+        assert_eq!((e.line, e.col), (1, 13), "{e}");
+    }
+}
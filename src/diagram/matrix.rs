@@ -1,7 +1,9 @@
-use crate::utils::unicode_decode;
-use artimonist::{Matrix, ToMatrix};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use super::editor;
+use super::format::{self, DiagramCell};
+use crate::utils::SecretString;
+use anyhow::{Context, anyhow};
+use artimonist::Matrix;
+use zeroize::Zeroize;
 
 pub trait LoadMatrix<T>
 where
@@ -9,74 +11,92 @@ where
 {
     /// load 7 * 7 matrix from file
     fn from_file(path: &str) -> anyhow::Result<Self>;
-    /// load 7 * 7 matrix from inquire
-    fn from_inquire() -> anyhow::Result<Self>;
+    /// load 7 * 7 matrix from the grid editor, entered twice to verify if `twice`
+    fn from_inquire(twice: bool) -> anyhow::Result<Self>;
+    /// save 7 * 7 matrix to a new file, readable by the owner only
+    fn to_file(&self, path: &str) -> anyhow::Result<()>;
 }
 
 impl<T> LoadMatrix<T> for Matrix<T, 7, 7>
 where
-    T: UniParser + std::fmt::Debug,
+    T: DiagramCell + PartialEq + Zeroize,
 {
     fn from_file(path: &str) -> anyhow::Result<Self> {
-        let mvs = BufReader::new(File::open(path)?)
-            .lines()
-            .take(7)
-            .map(|ln| {
-                if let Ok(str) = ln {
-                    UniParser::parse_values(&str)
-                } else {
-                    eprintln!("Error reading line: {}", ln.unwrap_err());
-                    vec![]
-                }
-            })
-            .collect::<Vec<_>>();
-        Ok(mvs.to_matrix())
+        let text = SecretString::new(std::fs::read_to_string(path)?);
+        format::parse(&text).with_context(|| format!("invalid diagram file `{path}`"))
     }
 
-    fn from_inquire() -> anyhow::Result<Self> {
-        let mut mvs: Vec<_> = vec![];
-        (1..=7).for_each(|i| {
-            let ln = inquire::Text::new(&format!("row ({i})"))
-                .with_initial_value(r#"""  "#.repeat(7).trim_end())
-                .with_help_message("Fill characters in quotes.")
-                .prompt();
-            if let Ok(ln) = ln {
-                mvs.push(UniParser::parse_values(&ln));
-            } else {
-                eprintln!("Error reading input for row {i}");
-            }
-        });
-        Ok(mvs.to_matrix())
+    fn from_inquire(twice: bool) -> anyhow::Result<Self> {
+        let mut mx = editor::edit(match T::KIND {
+            "simple" => "Simple diagram: a character per cell",
+            _ => "Complex diagram: up to 20 characters per cell",
+        })?;
+        if !twice {
+            return Ok(mx);
+        }
+
+        let mut again = editor::edit(match T::KIND {
+            "simple" => "Simple diagram, enter it again to verify",
+            _ => "Complex diagram, enter it again to verify",
+        })?;
+        let differ = diff_cells(&mx, &again);
+        again.zeroize();
+        if !differ.is_empty() {
+            mx.zeroize();
+            return Err(anyhow!(
+                "the two entries differ at cells {}, nothing is generated",
+                differ.join(" ")
+            ));
+        }
+        Ok(mx)
     }
-}
 
-trait UniParser
-where
-    Self: Sized,
-{
-    fn decode(s: &str) -> Option<Self>;
+    fn to_file(&self, path: &str) -> anyhow::Result<()> {
+        use std::io::Write;
 
-    fn parse_values(line: &str) -> Vec<Option<Self>> {
-        line.trim()
-            .strip_prefix('"')
-            .unwrap_or(line)
-            .strip_suffix('"')
-            .unwrap_or(line)
-            .split(r#""  ""#)
-            .take(7)
-            .map(Self::decode)
-            .collect::<Vec<_>>()
+        // the latest format composes cells, a decomposed one of a legacy file would re-key
+        if let Some(cell) = format::unnormalized(self) {
+            return Err(anyhow!(
+                "cell {cell} is not in NFC, a diagram file of format 1 would give other wallets; keep the legacy file"
+            ));
+        }
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut f = options
+            .open(path)
+            .with_context(|| format!("cannot create diagram file `{path}`"))?;
+        f.write_all(format::to_text(self).as_bytes())?;
+        Ok(())
     }
 }
 
-impl UniParser for char {
-    fn decode(s: &str) -> Option<char> {
-        unicode_decode(s).chars().next()
-    }
+/// Positions of the cells which differ between two diagrams, as `row,col`.
+fn diff_cells<T: PartialEq>(a: &Matrix<T, 7, 7>, b: &Matrix<T, 7, 7>) -> Vec<String> {
+    (0..49)
+        .map(|i| (i / 7, i % 7))
+        .filter(|&(r, c)| a[r][c] != b[r][c])
+        .map(|(r, c)| format!("{},{}", r + 1, c + 1))
+        .collect()
 }
 
-impl UniParser for String {
-    fn decode(s: &str) -> Option<String> {
-        Some(unicode_decode(s).chars().take(20).collect())
+#[cfg(test)]
+mod matrix_test {
+    use super::*;
+
+    #[test]
+    fn test_diff_cells() {
+        let mut a: Matrix<char, 7, 7> = [[None; 7]; 7];
+        a[0][3] = Some('A');
+        a[4][4] = Some('B');
+        let mut b = a;
+        assert!(diff_cells(&a, &b).is_empty());
+
+        b[0][3] = Some('a');
+        b[6][0] = Some('C');
+        assert_eq!(diff_cells(&a, &b), ["1,4", "7,1"]);
     }
 }
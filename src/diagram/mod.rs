@@ -1,6 +1,13 @@
 mod arg;
+mod editor;
 mod execute;
+mod format;
 mod matrix;
 mod output;
+mod strength;
 
-pub use arg::DiagramCommand;
+pub use arg::{DiagramCommand, GenerateTarget, PwdType};
+pub use editor::edit;
+pub use format::DiagramCell;
+pub use matrix::LoadMatrix;
+pub use output::{DeriveTargets, GridTable, fingerprint};
@@ -1,9 +1,18 @@
-use super::DiagramCommand;
-use crate::utils::unicode_encode;
-use anyhow::anyhow;
-use artimonist::{BIP38, BIP85, ComplexDiagram, GenericDiagram, Matrix, SimpleDiagram, Xpriv};
+use super::arg::LightningNode;
+use super::{DiagramCommand, GenerateTarget};
+use crate::bip85::{Bip85App, Bip85Keys, Bip85Lightning, GENESIS_TIME};
+use crate::utils::{
+    SecretString, SecretXpriv, display_width, nfc_secret, unicode_describe, unicode_encode,
+};
+use anyhow::{Context, anyhow};
+use artimonist::bitcoin::hashes::{Hash, sha256};
+use artimonist::bitcoin::hex::DisplayHex;
+use artimonist::bitcoin::secp256k1::Secp256k1;
+use artimonist::{
+    BIP38, BIP85, ComplexDiagram, GenericDiagram, Language, Matrix, SimpleDiagram, Xpriv, Xpub,
+};
 use std::io::{BufWriter, Write};
-use unicode_normalization::UnicodeNormalization;
+use std::ops::Range;
 
 pub trait ConsoleOutput<T: ToString>: GenericDiagram {
     fn matrix(&self) -> &Matrix<T, 7, 7>;
@@ -22,12 +31,25 @@ pub trait ConsoleOutput<T: ToString>: GenericDiagram {
             writeln!(f)?;
             writeln!(f, "Unicode View: ")?;
             writeln!(f, "{}", mx.fmt_table(true))?;
+            // characters of each cell as they were interpreted from input
+            for (r, row) in mx.iter().enumerate() {
+                for (c, cell) in row.iter().enumerate() {
+                    let Some(text) = cell.as_ref().map(T::to_string) else {
+                        continue;
+                    };
+                    if !text.is_ascii() {
+                        writeln!(f, "{},{}: {}", r + 1, c + 1, unicode_describe(&text))?;
+                    }
+                }
+            }
         }
 
         // generation results
         let password = cmd.password.as_ref().ok_or(anyhow!("empty password"))?;
-        let pass_nfc: String = password.nfc().collect();
-        let master = self.bip32_master(pass_nfc.as_bytes())?;
+        let pass_nfc = nfc_secret(password);
+        let master = SecretXpriv::new(self.bip32_master(pass_nfc.as_bytes())?);
+        writeln!(f)?;
+        writeln!(f, "Fingerprint: {}", fingerprint(&master))?;
         cmd.derive_all(&master, f)?;
 
         Ok(())
@@ -45,54 +67,109 @@ impl ConsoleOutput<String> for ComplexDiagram {
     }
 }
 
-trait DeriveTargets {
-    fn derive_all(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()>;
-    fn mnemonic(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()>;
-    fn wif(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()>;
-    fn xpriv(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()>;
-    fn pwd(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()>;
+/// Non-secret check of the diagram and password to compare between runs:
+/// the BIP32 master fingerprint and three words of the master public key hash.
+pub fn fingerprint(master: &Xpriv) -> String {
+    let secp = Secp256k1::signing_only();
+    let xpub = Xpub::from_priv(&secp, master);
+    let hash = sha256::Hash::hash(&xpub.encode());
+    // 33 bits of the hash as three words of 11 bits
+    let bits = u64::from_be_bytes(hash[..8].try_into().expect("8 bytes")) >> 31;
+    let words: Vec<_> = (0..3)
+        .rev()
+        .filter_map(|i| Language::English.word_at((bits >> (i * 11)) as usize & 0x7ff))
+        .collect();
+    format!("{} ({})", xpub.fingerprint(), words.join(" "))
 }
 
-impl<D: GenericDiagram> DeriveTargets for DiagramCommand<D> {
+/// BIP85 children of a master key, printed by the generation targets.
+pub trait DeriveTargets {
+    fn target(&self) -> &GenerateTarget;
+    /// BIP85 indices to generate
+    fn indices(&self) -> Range<u32>;
+    fn language(&self) -> Option<Language>;
+    /// Password encrypting the private keys by BIP38
+    fn encryption(&self) -> Option<&SecretString>;
+
+    #[inline(always)]
+    fn has_mnemonic(&self) -> bool {
+        let target = self.target();
+        target.mnemonic.is_some()
+            || !(target.wif
+                || target.xprv
+                || target.pwd
+                || target.hex.is_some()
+                || target.dice.is_some()
+                || target.ssh
+                || target.gpg
+                || target.age
+                || target.lightning.is_some())
+    }
+
     #[inline]
     fn derive_all(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()> {
         if self.has_mnemonic() {
             writeln!(f)?;
             self.mnemonic(master, f)?;
         }
-        if self.target.wif {
+        if self.target().wif {
             writeln!(f)?;
             self.wif(master, f)?;
         }
-        if self.target.xprv {
+        if self.target().xprv {
             writeln!(f)?;
             self.xpriv(master, f)?;
         }
-        if self.target.pwd {
+        if self.target().pwd {
             writeln!(f)?;
             self.pwd(master, f)?;
         }
+        if let Some(bytes) = self.target().hex {
+            writeln!(f)?;
+            self.hex(master, bytes, f)?;
+        }
+        if let Some(rolls) = self.target().dice {
+            writeln!(f)?;
+            self.dice(master, rolls, f)?;
+        }
+        if self.target().ssh {
+            writeln!(f)?;
+            self.ssh(master, f)?;
+        }
+        if self.target().gpg {
+            writeln!(f)?;
+            self.gpg(master, f)?;
+        }
+        if self.target().age {
+            writeln!(f)?;
+            self.age(master, f)?;
+        }
+        if let Some(node) = self.target().lightning {
+            writeln!(f)?;
+            self.lightning(master, node, f)?;
+        }
         Ok(())
     }
 
     #[inline]
     fn mnemonic(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()> {
         writeln!(f, "Mnemonics: ")?;
-        let length = self.target.mnemonic.unwrap_or(24) as u32;
-        for index in self.index..self.index + self.amount {
-            let language = self.language.ok_or(anyhow::anyhow!("unkown language"))?;
-            let mnemonic = master.bip85_mnemonic(language, length, index)?;
-            writeln!(f, "({index}): {mnemonic}")?;
+        let length = self.target().mnemonic.unwrap_or(24) as u32;
+        for index in self.indices() {
+            let language = self.language().ok_or(anyhow!("unkown language"))?;
+            let mnemonic = SecretString::new(master.bip85_mnemonic(language, length, index)?);
+            writeln!(f, "({index}): {}", mnemonic.as_str())?;
         }
         Ok(())
     }
 
     #[inline]
     fn wif(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()> {
-        let password = self.password.as_ref().ok_or(anyhow!("empty password"))?;
+        let password = self.encryption().ok_or(anyhow!("empty password"))?;
         writeln!(f, "Wifs: ")?;
-        for index in self.index..self.index + self.amount {
+        for index in self.indices() {
             let artimonist::Wif { addr, pk } = master.bip85_wif(index)?;
+            let pk = SecretString::new(pk);
             writeln!(f, "({index}): {addr}, {}", pk.bip38_encrypt(password)?)?;
         }
         Ok(())
@@ -101,9 +178,9 @@ impl<D: GenericDiagram> DeriveTargets for DiagramCommand<D> {
     #[inline]
     fn xpriv(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()> {
         writeln!(f, "Xprivs: ")?;
-        for index in self.index..self.index + self.amount {
-            let xpriv = master.bip85_xpriv(index)?;
-            writeln!(f, "({index}): {xpriv}")?;
+        for index in self.indices() {
+            let xpriv = SecretString::new(master.bip85_xpriv(index)?);
+            writeln!(f, "({index}): {}", xpriv.as_str())?;
         }
         Ok(())
     }
@@ -111,34 +188,247 @@ impl<D: GenericDiagram> DeriveTargets for DiagramCommand<D> {
     #[inline]
     fn pwd(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()> {
         writeln!(f, "Passwords: ")?;
-        for index in self.index..self.index + self.amount {
-            let pwd = master.bip85_pwd(Default::default(), 20, index)?;
-            writeln!(f, "({index}): {pwd}")?;
+        for index in self.indices() {
+            let target = self.target();
+            let pwd = SecretString::new(master.bip85_password(
+                target.pwd_type,
+                target.pwd_length,
+                index,
+            )?);
+            writeln!(f, "({index}): {}", pwd.as_str())?;
         }
         Ok(())
     }
+
+    #[inline]
+    fn hex(&self, master: &Xpriv, bytes: u32, f: &mut impl Write) -> anyhow::Result<()> {
+        writeln!(f, "Hex entropy: ")?;
+        for index in self.indices() {
+            let hex = SecretString::new(master.bip85_hex(bytes, index)?);
+            writeln!(f, "({index}): {}", hex.as_str())?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn dice(&self, master: &Xpriv, rolls: u32, f: &mut impl Write) -> anyhow::Result<()> {
+        writeln!(f, "Dice rolls: ")?;
+        for index in self.indices() {
+            let dice = SecretString::new(master.bip85_dice(self.target().sides, rolls, index)?);
+            writeln!(f, "({index}): {}", dice.as_str())?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn ssh(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()> {
+        writeln!(f, "SSH keys: ")?;
+        let comment = self.target().user_id.as_deref().unwrap_or_default();
+        for index in self.indices() {
+            let key = master.bip85_ssh(comment, index)?;
+            match &self.target().key_dir {
+                Some(dir) => {
+                    let name = format!("id_ed25519_{index}");
+                    let path = key_file(dir, &name, &[key.private.as_bytes(), b"\n"], true)?;
+                    key_file(
+                        dir,
+                        &format!("{name}.pub"),
+                        &[key.public.as_bytes(), b"\n"],
+                        false,
+                    )?;
+                    writeln!(f, "({index}): {}, {path}", key.public)?;
+                }
+                None => writeln!(f, "({index}): {}\n{}", key.public, key.private.as_str())?,
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn gpg(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()> {
+        let target = self.target();
+        let user_id = target.user_id.as_deref().ok_or(anyhow!("empty user id"))?;
+        let created = target.created.ok_or(anyhow!("empty creation time"))?;
+        writeln!(f, "OpenPGP keys: ")?;
+        for index in self.indices() {
+            let key = master.bip85_gpg(user_id, created, index)?;
+            match &target.key_dir {
+                Some(dir) => {
+                    let name = format!("gpg_{index}.asc");
+                    let path = key_file(dir, &name, &[key.private.as_bytes(), b"\n"], true)?;
+                    writeln!(f, "({index}): {}, {path}", key.fingerprint)?;
+                }
+                None => writeln!(
+                    f,
+                    "({index}): {}\n{}",
+                    key.fingerprint,
+                    key.private.as_str()
+                )?,
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn age(&self, master: &Xpriv, f: &mut impl Write) -> anyhow::Result<()> {
+        writeln!(f, "Age identities: ")?;
+        for index in self.indices() {
+            let key = master.bip85_age(index)?;
+            match &self.target().key_dir {
+                Some(dir) => {
+                    // the file of age-keygen, with the recipient as a comment
+                    let text = SecretString::new(format!(
+                        "# public key: {}\n{}",
+                        key.recipient,
+                        key.identity.as_str()
+                    ));
+                    let path = key_file(
+                        dir,
+                        &format!("age_{index}.txt"),
+                        &[text.as_bytes(), b"\n"],
+                        true,
+                    )?;
+                    writeln!(f, "({index}): {}, {path}", key.recipient)?;
+                }
+                None => writeln!(f, "({index}): {}, {}", key.recipient, key.identity.as_str())?,
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lightning(
+        &self,
+        master: &Xpriv,
+        node: LightningNode,
+        f: &mut impl Write,
+    ) -> anyhow::Result<()> {
+        let target = self.target();
+        let passphrase = target.seed_passphrase.as_ref().map(|p| p.as_str());
+        match node {
+            LightningNode::Cln => {
+                writeln!(f, "CLN hsm_secrets: ")?;
+                for index in self.indices() {
+                    let secret = master.bip85_hsm_secret(passphrase, index)?;
+                    match &target.key_dir {
+                        Some(dir) => {
+                            let path =
+                                key_file(dir, &format!("hsm_secret_{index}"), &[&secret], true)?;
+                            writeln!(f, "({index}): {path}")?;
+                        }
+                        None => {
+                            let hex = SecretString::new(secret.to_lower_hex_string());
+                            writeln!(f, "({index}): {}", hex.as_str())?;
+                        }
+                    }
+                }
+            }
+            LightningNode::Lnd => {
+                // days since the genesis block, the wallet scanning the chain from then
+                let birthday = target.birthday.unwrap_or(GENESIS_TIME);
+                let days = u16::try_from(birthday.saturating_sub(GENESIS_TIME) / 86400)?;
+                writeln!(f, "LND aezeeds: ")?;
+                for index in self.indices() {
+                    let seed = master.bip85_aezeed(days, passphrase.unwrap_or_default(), index)?;
+                    writeln!(f, "({index}): {}", seed.as_str())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write a new key file of the `parts` in `dir`, readable by the owner only if `secret`.
+fn key_file(dir: &str, name: &str, parts: &[&[u8]], secret: bool) -> anyhow::Result<String> {
+    let path = std::path::Path::new(dir).join(name);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if secret {
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = secret;
+
+    let mut file = options
+        .open(&path)
+        .with_context(|| format!("cannot create key file `{}`", path.display()))?;
+    for part in parts {
+        file.write_all(part)?;
+    }
+    Ok(path.display().to_string())
+}
+
+impl<D: GenericDiagram> DeriveTargets for DiagramCommand<D> {
+    fn target(&self) -> &GenerateTarget {
+        &self.target
+    }
+
+    fn indices(&self) -> Range<u32> {
+        self.index..self.index + self.amount
+    }
+
+    fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    fn encryption(&self) -> Option<&SecretString> {
+        self.password.as_ref()
+    }
 }
 
-trait ComfyTable<T> {
-    fn fmt_table(&self, unicode: bool) -> comfy_table::Table;
+pub trait GridTable<T> {
+    fn fmt_table(&self, unicode: bool) -> String;
 }
 
-impl<const H: usize, const W: usize, T> ComfyTable<T> for artimonist::Matrix<T, H, W>
+impl<const H: usize, const W: usize, T> GridTable<T> for artimonist::Matrix<T, H, W>
 where
     T: ToString,
 {
-    fn fmt_table(&self, unicode: bool) -> comfy_table::Table {
-        let mx = self.iter().map(|r| {
-            r.iter().map(|v| match v {
-                Some(x) => match unicode {
-                    true => unicode_encode(&x.to_string()),
-                    false => x.to_string(),
-                },
-                None => "".to_owned(),
+    /// Table of the cells padded by display width, so wide characters keep columns aligned.
+    fn fmt_table(&self, unicode: bool) -> String {
+        let cells: Vec<Vec<String>> = self
+            .iter()
+            .map(|r| {
+                r.iter()
+                    .map(|v| match v {
+                        Some(x) => match unicode {
+                            true => unicode_encode(&x.to_string()),
+                            false => x.to_string(),
+                        },
+                        None => "".to_owned(),
+                    })
+                    .collect()
+            })
+            .collect();
+        let widths: Vec<usize> = (0..W)
+            .map(|c| {
+                cells
+                    .iter()
+                    .map(|r| display_width(&r[c]))
+                    .max()
+                    .unwrap_or(0)
+                    .max(1)
             })
-        });
-        let mut table = comfy_table::Table::new();
-        table.add_rows(mx);
-        table
+            .collect();
+
+        let line = |edge: char, cross: char| {
+            let dashes: Vec<String> = widths.iter().map(|w| "-".repeat(w + 2)).collect();
+            format!("{edge}{}{edge}", dashes.join(&cross.to_string()))
+        };
+        let mut table = vec![line('+', '+')];
+        for (i, row) in cells.iter().enumerate() {
+            if i > 0 {
+                table.push(line('|', '+'));
+            }
+            let padded: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(text, w)| format!(" {text}{} ", " ".repeat(w - display_width(text))))
+                .collect();
+            table.push(format!("|{}|", padded.join("|")));
+        }
+        table.push(line('+', '+'));
+        table.join("\n")
     }
 }
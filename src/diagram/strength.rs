@@ -0,0 +1,273 @@
+//! Strength estimation of diagrams.
+//!
+//! The estimate counts the bits an attacker who knows the diagram method has
+//! to guess: which cells are filled and what they hold. Weak patterns reduce it.
+
+use super::format::DiagramCell;
+use artimonist::{Language, Matrix};
+use std::collections::HashMap;
+
+/// Diagrams below this estimate require `--accept-weak`
+pub const WEAK_BITS: f64 = 64.0;
+
+/// Bits of a dictionary word
+const WORD_BITS: f64 = 11.0;
+/// Bits of a keyboard run or sequence
+const RUN_BITS: f64 = 6.0;
+/// Bits of a character predictable from the previous one
+const REPEAT_BITS: f64 = 1.0;
+/// Bits of a word from large dictionaries, which guessers try for letters only text
+const TEXT_WORD_BITS: f64 = 18.0;
+
+/// Keyboard rows and alphabets for run detection
+const RUNS: &[&str] = &[
+    "1234567890",
+    "qwertyuiop",
+    "asdfghjkl",
+    "zxcvbnm",
+    "abcdefghijklmnopqrstuvwxyz",
+];
+/// Words well known to go with bitcoin, guessed before any dictionary
+const COMMON_WORDS: &[&str] = &[
+    "bitcoin",
+    "btc",
+    "satoshi",
+    "nakamoto",
+    "wallet",
+    "password",
+    "crypto",
+    "hodl",
+    "moon",
+    "artimonist",
+];
+
+/// Strength estimate of a diagram with the weak patterns found
+pub struct Strength {
+    pub bits: f64,
+    pub warnings: Vec<String>,
+}
+
+impl Strength {
+    pub fn estimate<T: DiagramCell>(mx: &Matrix<T, 7, 7>) -> Self {
+        let mut warnings = vec![];
+        let cells: Vec<((usize, usize), String)> = mx
+            .iter()
+            .enumerate()
+            .flat_map(|(r, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(c, v)| v.as_ref().map(|v| ((r, c), v.to_text())))
+            })
+            .collect();
+        if cells.is_empty() {
+            warnings.push("diagram is empty".into());
+            return Self {
+                bits: 0.0,
+                warnings,
+            };
+        }
+
+        let positions: Vec<_> = cells.iter().map(|(pos, _)| *pos).collect();
+        let mut bits = position_bits(&positions, &mut warnings);
+
+        let mut seen: HashMap<&str, (usize, usize)> = HashMap::new();
+        let mut run = 1;
+        let mut prev: Option<char> = None;
+        for ((r, c), text) in &cells {
+            let at = format!("cell {},{}", r + 1, c + 1);
+            let mut chars = text.chars();
+            let single = chars.next().filter(|_| chars.next().is_none());
+
+            // single characters in reading order, like 1 2 3 or a b c
+            let step = single.zip(prev).map(|(a, b)| a as i64 - b as i64);
+            prev = single;
+            if matches!(step, Some(-1 | 1)) {
+                run += 1;
+                if run == 3 {
+                    warnings.push(format!("{at} continues a sequence of characters"));
+                }
+                bits += REPEAT_BITS;
+                continue;
+            }
+            run = 1;
+
+            if let Some((r0, c0)) = seen.insert(text, (*r, *c)) {
+                warnings.push(format!("{at} repeats cell {},{}", r0 + 1, c0 + 1));
+                bits += REPEAT_BITS;
+            } else if single.is_none() && is_word(text) {
+                warnings.push(format!("{at} is a dictionary word"));
+                bits += WORD_BITS;
+            } else if text.chars().count() >= 3 && is_run(text) {
+                warnings.push(format!("{at} is a keyboard run or sequence"));
+                bits += RUN_BITS;
+            } else if text.chars().count() >= 3 && text.chars().all(char::is_alphabetic) {
+                bits += text_bits(text).min(TEXT_WORD_BITS);
+            } else {
+                bits += text_bits(text);
+            }
+        }
+        Self { bits, warnings }
+    }
+
+    pub fn is_weak(&self) -> bool {
+        self.bits < WEAK_BITS
+    }
+
+    pub fn rating(&self) -> &'static str {
+        match self.bits {
+            b if b < WEAK_BITS => "weak",
+            b if b < 96.0 => "fair",
+            b if b < 128.0 => "good",
+            _ => "strong",
+        }
+    }
+}
+
+/// Bits of choosing the filled cells, a straight line is easy to guess.
+fn position_bits(positions: &[(usize, usize)], warnings: &mut Vec<String>) -> f64 {
+    let k = positions.len();
+    let on_line =
+        |f: fn(&(usize, usize)) -> usize| positions.iter().map(f).all(|v| v == f(&positions[0]));
+    if k >= 3
+        && (on_line(|p| p.0)
+            || on_line(|p| p.1)
+            || on_line(|p| p.0 + 7 - p.1)
+            || on_line(|p| p.0 + p.1))
+    {
+        warnings.push("filled cells lie on a row, column or diagonal".into());
+        // any subset of a few lines
+        return (16.0 * binomial(7, k.min(7))).log2();
+    }
+    binomial(49, k).log2()
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+fn is_word(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    COMMON_WORDS.contains(&lower.as_str())
+        || Language::all()
+            .iter()
+            .any(|lang| lang.index_of(&lower).is_some())
+}
+
+/// Text is a part of a keyboard row or alphabet, forwards or backwards.
+fn is_run(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    let reversed: String = lower.chars().rev().collect();
+    RUNS.iter()
+        .any(|run| run.contains(&lower) || run.contains(&reversed))
+}
+
+/// Bits of free text: ascii characters by the classes in use, others by unicode block.
+fn text_bits(text: &str) -> f64 {
+    let (mut digit, mut lower, mut upper, mut other) = (false, false, false, false);
+    let mut ascii = 0;
+    let mut bits = 0.0;
+    let mut prev = None;
+    for ch in text.chars() {
+        if prev == Some(ch) {
+            bits += REPEAT_BITS;
+            continue;
+        }
+        prev = Some(ch);
+        match ch {
+            '0'..='9' => digit = true,
+            'a'..='z' => lower = true,
+            'A'..='Z' => upper = true,
+            ' '..='~' => other = true,
+            _ => {
+                bits += block_bits(ch);
+                continue;
+            }
+        }
+        ascii += 1;
+    }
+    let pool = [(digit, 10), (lower, 26), (upper, 26), (other, 33)]
+        .iter()
+        .filter(|(used, _)| *used)
+        .map(|(_, size)| size)
+        .sum::<u32>();
+    if ascii > 0 {
+        bits += ascii as f64 * (pool as f64).log2();
+    }
+    bits
+}
+
+/// Bits of a non-ascii character: guessers try the characters of its block.
+fn block_bits(ch: char) -> f64 {
+    match ch {
+        // CJK ideographs and hangul, a few thousands in common use
+        '\u{3400}'..='\u{9fff}' | '\u{ac00}'..='\u{d7af}' => 12.0,
+        // emoji, about a thousand popular ones
+        '\u{1f300}'..='\u{1faff}' => 10.0,
+        _ => 8.0,
+    }
+}
+
+#[cfg(test)]
+mod strength_test {
+    use super::*;
+
+    fn matrix<T>(cells: Vec<((usize, usize), T)>) -> Matrix<T, 7, 7> {
+        let mut mx: Matrix<T, 7, 7> = std::array::from_fn(|_| std::array::from_fn(|_| None));
+        for ((r, c), v) in cells {
+            mx[r][c] = Some(v);
+        }
+        mx
+    }
+
+    #[test]
+    fn test_weak_diagrams() {
+        // a digit in the corner
+        let s = Strength::estimate(&matrix(vec![((0, 6), '0')]));
+        assert!(s.is_weak(), "{}", s.bits);
+
+        // a diagonal of a sequence
+        let s = Strength::estimate(&matrix(
+            (0..7).map(|i| ((i, i), (b'1' + i as u8) as char)).collect(),
+        ));
+        assert!(s.is_weak(), "{}", s.bits);
+        assert!(s.warnings.iter().any(|w| w.contains("diagonal")));
+        assert!(s.warnings.iter().any(|w| w.contains("sequence")));
+
+        // well known words
+        let words = ["bitcoin", "satoshi", "nakamoto", "qwerty", "bitcoin"];
+        let s = Strength::estimate(&matrix(
+            words
+                .iter()
+                .enumerate()
+                .map(|(i, w)| ((i, 6 - i), w.to_string()))
+                .collect(),
+        ));
+        assert!(s.is_weak(), "{}", s.bits);
+        assert!(s.warnings.iter().any(|w| w.contains("dictionary word")));
+        assert!(s.warnings.iter().any(|w| w.contains("keyboard run")));
+        assert!(s.warnings.iter().any(|w| w.contains("repeats cell 1,7")));
+    }
+
+    #[test]
+    fn test_strong_diagram() {
+        let cells = ["【", "1", "$", "≈", "⅞", "£", "】", "🚀", "汉", "Zq"]
+            .iter()
+            .zip([
+                (0, 0),
+                (1, 4),
+                (2, 2),
+                (3, 6),
+                (4, 1),
+                (5, 3),
+                (6, 5),
+                (0, 3),
+                (3, 0),
+                (6, 0),
+            ])
+            .map(|(v, pos)| (pos, v.to_string()))
+            .collect();
+        let s = Strength::estimate(&matrix(cells));
+        assert!(!s.is_weak(), "{}", s.bits);
+        assert!(s.warnings.is_empty(), "{:?}", s.warnings);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::utils::{PasswordInput, SecretString};
 use artimonist::bitcoin;
 
 #[derive(clap::Parser)]
@@ -7,15 +8,19 @@ pub struct EncryptCommand<const ENCRYPT: bool> {
     pub source: EncryptSource,
 
     /// Password
-    #[clap(hide = true, long)]
-    pub password: Option<String>,
+    #[clap(hide = true, long, env = "ARTIMONIST_PASSWORD", hide_env_values = true)]
+    pub password: Option<SecretString>,
+
+    /// Password input channels
+    #[clap(flatten)]
+    pub password_input: PasswordInput,
 }
 
 /// Source of encryption/decryption
 #[derive(Clone, Debug)]
 pub enum EncryptSource {
     /// Private key in WIF format or encrypted key
-    Key(String),
+    Key(SecretString),
     /// Text file containing private keys or encrypted keys
     File(String),
 }
@@ -25,7 +30,7 @@ impl std::str::FromStr for EncryptSource {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if is_private_key(s) || is_encrypted_key(s) {
-            Ok(EncryptSource::Key(s.to_string()))
+            Ok(EncryptSource::Key(SecretString::new(s.to_owned())))
         } else if std::path::Path::new(s).exists() {
             Ok(EncryptSource::File(s.to_string()))
         } else {
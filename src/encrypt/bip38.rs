@@ -1,5 +1,6 @@
 use super::{EncryptCommand, arg::EncryptSource};
-use crate::{Execute, utils::inquire_password};
+use crate::Execute;
+use crate::utils::{SecretInput, SecretString, inquire_password, nfc_secret};
 use anyhow::anyhow;
 use artimonist::BIP38;
 use std::fs::File;
@@ -12,17 +13,18 @@ impl<const ENCRYPT: bool> Execute for EncryptCommand<ENCRYPT> {
         }
 
         // if no password is provided, prompt for it
-        let password = match &self.password {
-            Some(p) => p.to_string(),
-            None => inquire_password(false)?,
-        };
+        let password = self
+            .password_input
+            .resolve(self.password.as_ref(), || inquire_password(false))?;
+        let password = nfc_secret(&password);
 
         match &self.source {
             EncryptSource::Key(key) => {
                 if ENCRYPT {
                     println!("Encrypted private key: {}", key.bip38_encrypt(&password)?);
                 } else {
-                    println!("Decrypted private key: {}", key.bip38_decrypt(&password)?);
+                    let pk = SecretString::new(key.bip38_decrypt(&password)?);
+                    println!("Decrypted private key: {}", pk.as_str());
                 }
             }
             EncryptSource::File(file) => {
@@ -36,28 +38,26 @@ impl<const ENCRYPT: bool> Execute for EncryptCommand<ENCRYPT> {
 fn execute_bulk<const ENCRYPT: bool>(file: &str, password: &str) -> anyhow::Result<()> {
     let f = &mut BufWriter::new(std::io::stdout());
     for ln in BufReader::new(File::open(file)?).lines() {
-        let line = ln?;
+        let line = SecretString::new(ln?);
         if line
             .split_ascii_whitespace()
             .any(|s| (ENCRYPT && s.is_private()) || (!ENCRYPT && s.is_encrypted()))
         {
-            let new_line = line
-                .split_ascii_whitespace()
-                .map(|s| {
-                    if ENCRYPT && s.is_private() {
-                        s.bip38_encrypt(password).unwrap_or(s.to_string())
-                    } else if s.is_encrypted() {
-                        s.bip38_decrypt(password).unwrap_or(s.to_string())
-                    } else {
-                        s.to_string()
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join(" ");
-            writeln!(f, "{new_line}")?;
+            for (i, s) in line.split_ascii_whitespace().enumerate() {
+                let word = SecretString::new(if ENCRYPT && s.is_private() {
+                    s.bip38_encrypt(password).unwrap_or(s.to_string())
+                } else if s.is_encrypted() {
+                    s.bip38_decrypt(password).unwrap_or(s.to_string())
+                } else {
+                    s.to_string()
+                });
+                let sep = if i > 0 { " " } else { "" };
+                write!(f, "{sep}{}", word.as_str())?;
+            }
+            writeln!(f)?;
             f.flush()?;
         } else {
-            writeln!(f, "{line}")?;
+            writeln!(f, "{}", line.as_str())?;
         }
     }
     Ok(())
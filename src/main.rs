@@ -1,15 +1,25 @@
 mod bip32;
+mod bip85;
 mod derive;
 mod diagram;
 mod encrypt;
+mod pwd;
+mod recover;
+mod train;
 mod utils;
+mod which;
 
 use artimonist::{ComplexDiagram, SimpleDiagram};
 use bip32::Bip32Command;
+use bip85::Bip85Command;
 use clap::{Parser, Subcommand};
 use derive::DeriveCommand;
 use diagram::DiagramCommand;
 use encrypt::EncryptCommand;
+use pwd::PwdCommand;
+use recover::{RecoverDiagramCommand, RecoverMnemonicCommand, RecoverPasswordCommand};
+use train::TrainCommand;
+use which::WhichIndexCommand;
 
 /// Artimonist - A tool for generating mnemonics and wallets.   
 #[derive(Parser)]
@@ -17,6 +27,10 @@ use encrypt::EncryptCommand;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Never prompt, fail on missing input [default when stdin is not a terminal]
+    #[clap(long, global = true)]
+    pub no_input: bool,
 }
 
 #[derive(Subcommand)]
@@ -33,23 +47,61 @@ pub enum Commands {
     Derive(DeriveCommand),
     /// Derive by custom bip32 path
     Bip32(Bip32Command),
+    /// Generate BIP85 children of a mnemonic or master key
+    Bip85(Bip85Command),
+    /// Generate the password of a site by its label
+    Pwd(PwdCommand),
+    /// Memorize a diagram by recall quizzes
+    Train(TrainCommand),
+    /// Search nearby variants of a mistyped diagram
+    RecoverDiagram(RecoverDiagramCommand),
+    /// Find missing, misspelled or swapped words of a mnemonic
+    RecoverMnemonic(RecoverMnemonicCommand),
+    /// Find the password of a diagram or mnemonic among variants of candidates
+    RecoverPassword(RecoverPasswordCommand),
+    /// Find the BIP85 index a mnemonic, wallet, xprv or password was generated at
+    WhichIndex(WhichIndexCommand),
 }
 
 pub trait Execute {
     fn execute(&mut self) -> anyhow::Result<()>;
 }
 
+/// Exit code of missing input in non-interactive mode
+const EXIT_MISSING_INPUT: i32 = 3;
+
 fn main() -> anyhow::Result<()> {
+    use std::io::IsTerminal;
+
+    if let Err(e) = utils::harden_process() {
+        eprintln!("Warning: core dumps are not disabled: {e}");
+    }
+
     let args = Cli::parse();
-    match args.command {
-        Commands::Simple(mut cmd) => cmd.execute()?,
-        Commands::Complex(mut cmd) => cmd.execute()?,
-        Commands::Encrypt(mut cmd) => cmd.execute()?,
-        Commands::Decrypt(mut cmd) => cmd.execute()?,
-        Commands::Derive(mut cmd) => cmd.execute()?,
-        Commands::Bip32(mut cmd) => cmd.execute()?,
+    utils::set_interactive(!args.no_input && std::io::stdin().is_terminal());
+
+    let result = match args.command {
+        Commands::Simple(mut cmd) => cmd.execute(),
+        Commands::Complex(mut cmd) => cmd.execute(),
+        Commands::Encrypt(mut cmd) => cmd.execute(),
+        Commands::Decrypt(mut cmd) => cmd.execute(),
+        Commands::Derive(mut cmd) => cmd.execute(),
+        Commands::Bip32(mut cmd) => cmd.execute(),
+        Commands::Bip85(mut cmd) => cmd.execute(),
+        Commands::Pwd(mut cmd) => cmd.execute(),
+        Commands::Train(mut cmd) => cmd.execute(),
+        Commands::RecoverDiagram(mut cmd) => cmd.execute(),
+        Commands::RecoverMnemonic(mut cmd) => cmd.execute(),
+        Commands::RecoverPassword(mut cmd) => cmd.execute(),
+        Commands::WhichIndex(mut cmd) => cmd.execute(),
+    };
+    if let Err(e) = &result
+        && let Some(missing) = e.downcast_ref::<utils::MissingInput>()
+    {
+        eprintln!("Error: {missing}");
+        std::process::exit(EXIT_MISSING_INPUT);
     }
-    Ok(())
+    result
 }
 
 const CMD_ABOUT: &str = "
@@ -57,7 +109,13 @@ Artimonist
 A tool for generating mnemonics and wallets.
 
 Project location: <https://github.com/artimonist/cli>
-Web version: <https://www.artimonist.org>";
+Web version: <https://www.artimonist.org>
+
+Exit codes:
+  0  Success
+  1  Error
+  2  Invalid arguments
+  3  Missing input with --no-input or stdin not a terminal";
 
 #[cfg(not(feature = "testnet"))]
 #[cfg(test)]
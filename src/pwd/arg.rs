@@ -0,0 +1,50 @@
+use crate::diagram::PwdType;
+use crate::utils::{PasswordInput, SecretString};
+use crate::which::IndexSource;
+
+#[derive(clap::Parser, Debug)]
+pub struct PwdCommand {
+    /// Diagram file or master key of the passwords
+    #[clap(name = "DIAGRAM|MASTER_KEY")]
+    pub source: IndexSource,
+
+    /// Label of the site, as a domain or url
+    #[clap(long, value_name = "LABEL", required_unless_present = "list")]
+    pub site: Option<String>,
+
+    /// Counter of the site's password, the stored one or 1 if omitted
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..), requires = "site")]
+    pub counter: Option<u32>,
+
+    /// Password length, the stored one or 20 if omitted
+    #[clap(long, requires = "site")]
+    pub length: Option<u32>,
+
+    /// Password characters, the stored ones or distinct if omitted
+    #[clap(long, value_name = "TYPE", requires = "site")]
+    pub pwd_type: Option<PwdType>,
+
+    /// Increase the stored counter of the site, for a new password
+    #[clap(long, requires_all = ["site", "sites"], conflicts_with = "counter")]
+    pub rotate: bool,
+
+    /// Index file of site labels and counters, it never holds passwords
+    #[clap(long, value_name = "FILE")]
+    pub sites: Option<String>,
+
+    /// List the passwords of all sites in the index file
+    #[clap(long, requires = "sites", conflicts_with = "site")]
+    pub list: bool,
+
+    /// The diagram is complex
+    #[clap(long)]
+    pub complex: bool,
+
+    /// Password as salt
+    #[clap(hide = true, long, env = "ARTIMONIST_PASSWORD", hide_env_values = true)]
+    pub password: Option<SecretString>,
+
+    /// Password input channels
+    #[clap(flatten)]
+    pub password_input: PasswordInput,
+}
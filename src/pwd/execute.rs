@@ -0,0 +1,83 @@
+use super::arg::PwdCommand;
+use super::sites::{Site, SiteList, normalize};
+use crate::bip85::Bip85App;
+use crate::utils::{SecretInput, SecretString, SecretXpriv, inquire_password};
+use anyhow::bail;
+
+impl crate::Execute for PwdCommand {
+    fn execute(&mut self) -> anyhow::Result<()> {
+        let mut list = self.sites.as_deref().map(SiteList::load).transpose()?;
+        if self.list {
+            let list = list.expect("--list requires --sites");
+            if list.sites.is_empty() {
+                bail!(
+                    "no sites in `{}`",
+                    self.sites.as_deref().unwrap_or_default()
+                );
+            }
+            let master = self.master()?;
+            println!("Passwords: ");
+            for site in &list.sites {
+                let pwd = password(&master, site)?;
+                println!("{} #{}: {}", site.label, site.counter, pwd.as_str());
+            }
+            return Ok(());
+        }
+
+        let label = normalize(self.site.as_deref().expect("--site or --list"));
+        if label.is_empty() {
+            bail!("the site label is empty");
+        }
+        let stored = list.as_ref().and_then(|l| l.get(&label)).cloned();
+        let counter = match (&stored, self.rotate) {
+            (Some(site), true) => site.counter + 1,
+            (None, true) => bail!("no site `{label}` to rotate in the index file"),
+            (Some(site), false) => self.counter.unwrap_or(site.counter),
+            (None, false) => self.counter.unwrap_or(1),
+        };
+        let site = Site {
+            label,
+            counter,
+            length: self
+                .length
+                .or(stored.as_ref().map(|s| s.length))
+                .unwrap_or(20),
+            pwd_type: self
+                .pwd_type
+                .or(stored.as_ref().map(|s| s.pwd_type))
+                .unwrap_or_default(),
+        };
+        site.pwd_type.validate(site.length)?;
+
+        let master = self.master()?;
+        let pwd = password(&master, &site)?;
+        println!("Site: {}", site.label);
+        println!("Counter: {}", site.counter);
+        println!("Index: {}", site.index());
+        println!("Password: {}", pwd.as_str());
+
+        if let Some(list) = &mut list
+            && stored.as_ref() != Some(&site)
+        {
+            list.set(site);
+            list.save()?;
+        }
+        Ok(())
+    }
+}
+
+impl PwdCommand {
+    fn master(&self) -> anyhow::Result<SecretXpriv> {
+        let (master, _) = self.source.master(self.complex, || {
+            self.password_input
+                .resolve(self.password.as_ref(), || inquire_password(true))
+        })?;
+        Ok(master)
+    }
+}
+
+/// Password of a site, the BIP85 password at the index of its label and counter.
+fn password(master: &SecretXpriv, site: &Site) -> anyhow::Result<SecretString> {
+    let pwd = master.bip85_password(site.pwd_type, site.length, site.index())?;
+    Ok(SecretString::new(pwd))
+}
@@ -0,0 +1,5 @@
+mod arg;
+mod execute;
+mod sites;
+
+pub use arg::PwdCommand;
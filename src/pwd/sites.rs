@@ -0,0 +1,150 @@
+//! Labels of sites and the BIP85 indices of their passwords.
+
+use crate::diagram::PwdType;
+use anyhow::anyhow;
+use artimonist::bitcoin::hashes::{Hash, sha256};
+use clap::ValueEnum;
+use unicode_normalization::UnicodeNormalization;
+
+/// Password settings of a site, everything but the password itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Site {
+    pub label: String,
+    pub counter: u32,
+    pub length: u32,
+    pub pwd_type: PwdType,
+}
+
+impl Site {
+    /// BIP85 index of the password: the label and counter hashed below 2^31.
+    pub fn index(&self) -> u32 {
+        let hash = sha256::Hash::hash(format!("{}\n{}", self.label, self.counter).as_bytes());
+        u32::from_be_bytes(hash[..4].try_into().expect("4 bytes")) >> 1
+    }
+}
+
+/// Label of a site as typed or as an url, with or without a scheme:
+/// `https://www.Example.com/login` and `example.com/login` are `example.com`.
+pub fn normalize(label: &str) -> String {
+    let label: String = label.nfkc().collect::<String>().to_lowercase();
+    let label = label.trim();
+    let host = |url: &str| {
+        url.split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_owned()
+    };
+    let label = match label.split_once("://") {
+        Some((_, rest)) => host(rest).rsplit('@').next().unwrap_or_default().to_owned(),
+        // a single word of a dotted host, `me@example.com` kept as it is
+        None if !label.contains(char::is_whitespace) && host(label).contains('.') => host(label),
+        None => label.split_whitespace().collect::<Vec<_>>().join(" "),
+    };
+    match label.strip_prefix("www.") {
+        Some(host) if host.contains('.') => host.to_owned(),
+        _ => label,
+    }
+}
+
+/// Index file of sites, holding labels and settings but never passwords.
+pub struct SiteList {
+    path: String,
+    pub sites: Vec<Site>,
+}
+
+impl SiteList {
+    /// Sites of the index file, none if it does not exist yet.
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut sites = vec![];
+        let lines = text.lines().enumerate();
+        for (n, line) in lines.filter(|(_, ln)| !ln.starts_with('#') && !ln.trim().is_empty()) {
+            let invalid = || anyhow!("invalid site at line {} of `{path}`", n + 1);
+            let mut fields = line.splitn(4, ' ');
+            let mut next = || fields.next().ok_or_else(invalid);
+            let counter = next()?.parse().map_err(|_| invalid())?;
+            let length = next()?.parse().map_err(|_| invalid())?;
+            let pwd_type = PwdType::from_str(next()?, true).map_err(|_| invalid())?;
+            let label = normalize(next()?);
+            sites.push(Site {
+                label,
+                counter,
+                length,
+                pwd_type,
+            });
+        }
+        Ok(Self {
+            path: path.to_owned(),
+            sites,
+        })
+    }
+
+    pub fn get(&self, label: &str) -> Option<&Site> {
+        self.sites.iter().find(|s| s.label == label)
+    }
+
+    /// Add a site or replace the one of the same label.
+    pub fn set(&mut self, site: Site) {
+        match self.sites.iter_mut().find(|s| s.label == site.label) {
+            Some(s) => *s = site,
+            None => self.sites.push(site),
+        }
+    }
+
+    /// Save the sites, replacing the file as a whole.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let mut text = String::from(
+            "# Artimonist pwd sites: labels only, passwords are derived again from the diagram\n# counter length type label\n",
+        );
+        for site in &self.sites {
+            let pwd_type = site.pwd_type.to_possible_value().expect("not skipped");
+            text += &format!(
+                "{} {} {} {}\n",
+                site.counter,
+                site.length,
+                pwd_type.get_name(),
+                site.label
+            );
+        }
+        let temp = format!("{}.tmp", self.path);
+        std::fs::write(&temp, text)?;
+        std::fs::rename(&temp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod sites_test {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Payroll.Example.com "), "payroll.example.com");
+        assert_eq!(
+            normalize("https://user@www.Example.com:8443/login?next=1"),
+            "example.com:8443"
+        );
+        assert_eq!(normalize("ｂａｎｋ  pin"), "bank pin");
+        assert_eq!(normalize("www.example.com"), "example.com");
+        assert_eq!(normalize("Example.com/login"), "example.com");
+        assert_eq!(normalize("me@example.com"), "me@example.com");
+        assert_eq!(normalize("www.com"), "www.com");
+    }
+
+    #[test]
+    fn test_index() {
+        let site = |counter| Site {
+            label: "payroll.example.com".to_owned(),
+            counter,
+            length: 20,
+            pwd_type: PwdType::Distinct,
+        };
+        assert!(site(1).index() < 1 << 31);
+        assert_ne!(site(1).index(), site(2).index());
+        assert_eq!(site(1).index(), site(1).index());
+    }
+}
@@ -0,0 +1,251 @@
+use crate::utils::{KeyInput, PassphraseInput, PasswordInput, SecretInput, SecretString};
+use crate::utils::{inquire_key, inquire_passphrase, nfkd_secret};
+use artimonist::Language;
+use artimonist::bitcoin::bip32::Fingerprint;
+
+#[derive(clap::Parser, Debug)]
+pub struct RecoverDiagramCommand {
+    /// Diagram file as remembered
+    #[clap(name = "FILE")]
+    pub file: String,
+
+    /// The diagram is complex
+    #[clap(long)]
+    pub complex: bool,
+
+    /// What the diagram generated
+    #[command(flatten)]
+    pub target: RecoverTarget,
+
+    /// Start index to compare an address or mnemonic with
+    #[clap(short, long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..65536))]
+    pub index: u32,
+
+    /// Amount of indices to compare an address or mnemonic with
+    #[clap(short = 'm', long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..65536))]
+    pub amount: u32,
+
+    /// Edits to search up to, each alters, fills, empties, swaps or moves cells
+    #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub edits: u8,
+
+    /// Text file of candidate cell values, one per line
+    #[clap(long, value_name = "FILE")]
+    pub candidates: Option<String>,
+
+    /// Threads to search on, each takes 256 MiB of memory [default: all cores]
+    #[clap(long)]
+    pub threads: Option<usize>,
+
+    /// Save the recovered diagram to a new file in plaintext
+    #[clap(long, value_name = "FILE")]
+    pub save: Option<String>,
+
+    /// Password as salt
+    #[clap(hide = true, long, env = "ARTIMONIST_PASSWORD", hide_env_values = true)]
+    pub password: Option<SecretString>,
+
+    /// Password input channels
+    #[clap(flatten)]
+    pub password_input: PasswordInput,
+}
+
+#[derive(clap::Args, Debug)]
+#[group(required = true, multiple = false)]
+pub struct RecoverTarget {
+    /// Master key fingerprint printed with the diagram's results
+    #[clap(long)]
+    pub fingerprint: Option<Fingerprint>,
+
+    /// Wallet address generated by the diagram
+    #[clap(long)]
+    pub address: Option<String>,
+
+    /// Mnemonic generated by the diagram
+    #[clap(long, value_name = "WORDS")]
+    pub mnemonic: Option<SecretString>,
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct RecoverMnemonicCommand {
+    /// Mnemonic with `?` for a missing word and `word?` for an uncertain one, prompt if omitted
+    #[clap(name = "MNEMONIC", env = "ARTIMONIST_KEY", hide_env_values = true)]
+    pub phrase: Option<SecretString>,
+
+    /// Mnemonic language [default: detected from the words]
+    #[clap(long)]
+    pub language: Option<Language>,
+
+    /// Also try each two words swapped
+    #[clap(long)]
+    pub swapped: bool,
+
+    /// What the mnemonic generated, checked by BIP44, BIP49 and BIP84 paths
+    #[command(flatten)]
+    pub target: WalletTarget,
+
+    /// Account of the address or xpub
+    #[clap(short, long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..65536))]
+    pub account: u32,
+
+    /// Amount of addresses to compare from index 0
+    #[clap(short = 'm', long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..65536))]
+    pub amount: u32,
+
+    /// Candidates to list without an address or xpub
+    #[clap(long, default_value_t = 100)]
+    pub limit: usize,
+
+    /// Threads to search on [default: all cores]
+    #[clap(long)]
+    pub threads: Option<usize>,
+
+    /// BIP39 passphrase of mnemonic
+    #[clap(long, env = "ARTIMONIST_PASSPHRASE", hide_env_values = true)]
+    pub passphrase: Option<SecretString>,
+
+    /// Passphrase input channels
+    #[clap(flatten)]
+    pub passphrase_input: PassphraseInput,
+
+    /// Use mnemonic without BIP39 passphrase
+    #[clap(long, conflicts_with_all = ["passphrase-stdin", "passphrase-fd", "passphrase-file"])]
+    pub no_passphrase: bool,
+
+    /// Mnemonic input channels
+    #[clap(flatten)]
+    pub key_input: KeyInput,
+}
+
+#[derive(clap::Args, Debug)]
+#[group(required = false, multiple = false)]
+pub struct WalletTarget {
+    /// Wallet address generated by the mnemonic
+    #[clap(long)]
+    pub address: Option<String>,
+
+    /// Account xpub, ypub or zpub of the mnemonic
+    #[clap(long)]
+    pub xpub: Option<String>,
+}
+
+impl RecoverMnemonicCommand {
+    /// Resolve the phrase from argument, input channels or masked prompt
+    pub fn phrase_text(&self) -> anyhow::Result<SecretString> {
+        match (self.key_input.read_secret()?, &self.phrase) {
+            (Some(text), _) => Ok(text),
+            (None, Some(text)) => Ok(text.clone()),
+            (None, None) => self.key_input.prompt(inquire_key),
+        }
+    }
+
+    /// BIP39 passphrase in NFKD, prompt for it if not provided.
+    pub fn passphrase(&self) -> anyhow::Result<SecretString> {
+        if self.no_passphrase {
+            return Ok(SecretString::default());
+        }
+        let passphrase = self
+            .passphrase_input
+            .resolve(self.passphrase.as_ref(), inquire_passphrase)?;
+        Ok(nfkd_secret(&passphrase))
+    }
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct RecoverPasswordCommand {
+    /// Diagram file or mnemonic the password was used with, not needed by --bip38
+    #[clap(
+        name = "DIAGRAM|MNEMONIC",
+        required_unless_present = "bip38",
+        conflicts_with = "bip38"
+    )]
+    pub source: Option<PasswordSource>,
+
+    /// The diagram is complex
+    #[clap(long)]
+    pub complex: bool,
+
+    /// Text file of base passwords, one per line
+    #[clap(long, value_name = "FILE")]
+    pub wordlist: String,
+
+    /// Mutations of each base password, comma separated
+    #[clap(long, value_delimiter = ',', default_value = "case,digits")]
+    pub rules: Vec<Rule>,
+
+    /// What the password generated
+    #[command(flatten)]
+    pub target: PasswordTarget,
+
+    /// Start index of BIP85 addresses of a diagram
+    #[clap(short, long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..65536))]
+    pub index: u32,
+
+    /// Amount of addresses to compare
+    #[clap(short = 'm', long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..65536))]
+    pub amount: u32,
+
+    /// Account of a mnemonic's address
+    #[clap(short, long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..65536))]
+    pub account: u32,
+
+    /// Threads to search on, each takes 256 MiB of memory for a diagram [default: all cores]
+    #[clap(long)]
+    pub threads: Option<usize>,
+
+    /// File to save the progress to and resume the search from
+    #[clap(long, value_name = "FILE")]
+    pub checkpoint: Option<String>,
+}
+
+/// Secret the password belongs to
+#[derive(Clone, Debug)]
+pub enum PasswordSource {
+    /// Diagram file, the password is its salt
+    Diagram(String),
+    /// Mnemonic, the password is its BIP39 passphrase
+    Mnemonic(SecretString),
+}
+
+impl std::str::FromStr for PasswordSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if std::path::Path::new(s).exists() {
+            Ok(PasswordSource::Diagram(s.to_owned()))
+        } else if s.split_whitespace().count() >= 12 {
+            Ok(PasswordSource::Mnemonic(SecretString::new(s.to_owned())))
+        } else {
+            Err(format!("no diagram file or mnemonic: {s}"))
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+#[group(required = true, multiple = false)]
+pub struct PasswordTarget {
+    /// Master key fingerprint of the diagram or mnemonic
+    #[clap(long)]
+    pub fingerprint: Option<Fingerprint>,
+
+    /// Wallet address generated with the password
+    #[clap(long)]
+    pub address: Option<String>,
+
+    /// Private key encrypted by the password
+    #[clap(long, value_name = "KEY")]
+    pub bip38: Option<String>,
+}
+
+/// Mutation of a base password, each also keeps it as is
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Rule {
+    /// Character forms decoded, or non-ascii characters typed as `\u{..}`
+    Forms,
+    /// Letters replaced by lookalike digits and symbols, like `p@ssw0rd`
+    Leet,
+    /// Lowercase, uppercase and capitalized
+    Case,
+    /// One or two digits appended
+    Digits,
+}
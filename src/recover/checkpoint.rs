@@ -0,0 +1,79 @@
+//! Progress of a search saved to a file, to resume it after an interruption.
+
+use anyhow::{anyhow, bail};
+use artimonist::bitcoin::hashes::{Hash, sha256};
+use artimonist::bitcoin::hex::DisplayHex;
+use zeroize::Zeroize;
+
+/// Checked candidates of a search, kept with an id of the search
+/// so a checkpoint of another wordlist, rules or target is not resumed.
+pub struct Checkpoint {
+    path: String,
+    search: String,
+}
+
+impl Checkpoint {
+    /// Checkpoint of the search described by `parts`.
+    pub fn new(path: &str, parts: &[&[u8]]) -> Self {
+        // reserved, so the wordlist is not copied by reallocation
+        let mut data = Vec::with_capacity(parts.iter().map(|p| p.len() + 8).sum());
+        for part in parts {
+            // length prefixed, so parts cannot run into each other
+            data.extend((part.len() as u64).to_be_bytes());
+            data.extend(*part);
+        }
+        let hash = sha256::Hash::hash(&data);
+        data.zeroize();
+        Self {
+            path: path.to_owned(),
+            search: hash[..8].to_lower_hex_string(),
+        }
+    }
+
+    /// Candidates checked by an earlier run, 0 without a checkpoint file.
+    pub fn load(&self) -> anyhow::Result<u64> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let invalid = || anyhow!("invalid checkpoint `{}`", self.path);
+        let mut search = None;
+        let mut checked = None;
+        for line in text.lines().filter(|ln| !ln.starts_with('#')) {
+            let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+            match key.trim() {
+                "search" => search = Some(value.trim().to_owned()),
+                "checked" => checked = Some(value.trim().parse::<u64>().map_err(|_| invalid())?),
+                _ => return Err(invalid()),
+            }
+        }
+        if search.ok_or_else(invalid)? != self.search {
+            bail!(
+                "checkpoint `{}` is of another search, delete it to start over",
+                self.path
+            );
+        }
+        checked.ok_or_else(invalid)
+    }
+
+    /// Save the count of checked candidates, replacing the file as a whole.
+    pub fn save(&self, checked: u64) -> anyhow::Result<()> {
+        let text = format!(
+            "# Artimonist recover-password checkpoint, delete it to start over\nsearch = {}\nchecked = {checked}\n",
+            self.search
+        );
+        let temp = format!("{}.tmp", self.path);
+        std::fs::write(&temp, text)?;
+        std::fs::rename(&temp, &self.path)?;
+        Ok(())
+    }
+
+    /// Remove the checkpoint of a finished search.
+    pub fn remove(&self) -> anyhow::Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,524 @@
+use super::arg::{
+    PasswordSource, RecoverDiagramCommand, RecoverMnemonicCommand, RecoverPasswordCommand,
+};
+use super::checkpoint::Checkpoint;
+use super::mutation::Candidates;
+use super::phrase::Phrase;
+use super::variant::{Edit, VariantCell};
+use crate::derive::DerivePath;
+use crate::diagram::{GridTable, LoadMatrix, fingerprint};
+use crate::utils::{SecretInput, SecretString, SecretXpriv, inquire_password};
+use crate::utils::{duration_text, nfc_secret, nfkd_secret, parallel_search, parallel_search_from};
+use crate::utils::{unicode_decode, unicode_decode_forms, unicode_encode};
+use anyhow::{anyhow, bail};
+use artimonist::bitcoin::base58;
+use artimonist::bitcoin::bip32::Fingerprint;
+use artimonist::bitcoin::secp256k1::Secp256k1;
+use artimonist::{BIP38, BIP39, BIP85, Matrix, Mnemonic, Xpriv};
+use std::ops::Range;
+use std::str::FromStr;
+use std::time::Instant;
+use zeroize::{Zeroize, Zeroizing};
+
+impl crate::Execute for RecoverDiagramCommand {
+    fn execute(&mut self) -> anyhow::Result<()> {
+        match self.complex {
+            false => self.recover::<char>(),
+            true => self.recover::<String>(),
+        }
+    }
+}
+
+/// What the diagram generated, to recognize it by
+enum Target {
+    Fingerprint(Fingerprint),
+    Address(String),
+    Mnemonic(Mnemonic, SecretString),
+}
+
+impl Target {
+    /// Whether the master key generates the target at any of the BIP85 indices.
+    fn matches(&self, master: &Xpriv, indices: &Range<u32>) -> anyhow::Result<bool> {
+        match self {
+            Target::Fingerprint(fp) => Ok(master.fingerprint(&Secp256k1::signing_only()) == *fp),
+            Target::Address(addr) => {
+                for index in indices.clone() {
+                    if master.bip85_wif(index)?.addr == *addr {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Target::Mnemonic(mnemonic, words) => {
+                let length = mnemonic.count() as u32;
+                for index in indices.clone() {
+                    let generated = SecretString::new(master.bip85_mnemonic(
+                        mnemonic.language(),
+                        length,
+                        index,
+                    )?);
+                    if generated.as_str() == words.as_str() {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+        }
+    }
+}
+
+impl RecoverDiagramCommand {
+    fn recover<T: VariantCell>(&self) -> anyhow::Result<()> {
+        let mut mx = Matrix::<T, 7, 7>::from_file(&self.file)?;
+        let candidates = self.candidates::<T>()?;
+        let target = self.target()?;
+        let password = self
+            .password_input
+            .resolve(self.password.as_ref(), || inquire_password(true))?;
+        let salt = nfc_secret(&password);
+        let indices = self.index..self.index + self.amount;
+
+        // the diagram as remembered first, its time gives the estimate
+        let start = Instant::now();
+        let master = T::master(&mx, salt.as_bytes())?;
+        if target.matches(&master, &indices)? {
+            mx.zeroize();
+            println!("The diagram as remembered matches, nothing to recover.");
+            println!("Fingerprint: {}", fingerprint(&master));
+            return Ok(());
+        }
+        let each = start.elapsed().as_secs_f64();
+
+        let edits = Edit::all(&mx, &candidates);
+        let mut pairs: Vec<(usize, usize)> = vec![];
+        if self.edits > 1 {
+            for (i, a) in edits.iter().enumerate() {
+                for (j, b) in edits.iter().enumerate().skip(i + 1) {
+                    if a.disjoint(b) {
+                        pairs.push((i, j));
+                    }
+                }
+            }
+        }
+        let total = edits.len() + pairs.len();
+        let threads = threads(self.threads);
+        let within = match self.edits {
+            1 => "1 edit".to_owned(),
+            n => format!("{n} edits"),
+        };
+        eprintln!(
+            "Searching {total} variants within {within} on {threads} threads, about {}",
+            duration_text(each * total as f64 / threads as f64)
+        );
+
+        // edits of a variant: single ones, then disjoint pairs
+        let chosen = |i: usize| match i.checked_sub(edits.len()) {
+            None => vec![&edits[i]],
+            Some(p) => vec![&edits[pairs[p].0], &edits[pairs[p].1]],
+        };
+        let found = parallel_search(total as u64, threads, |i| {
+            let mut variant = mx.clone();
+            chosen(i as usize)
+                .iter()
+                .for_each(|e| e.apply(&mut variant));
+            let master = T::master(&variant, salt.as_bytes());
+            variant.zeroize();
+            Ok(target.matches(&master?, &indices)?.then_some(i as usize))
+        });
+        let Some(i) = found? else {
+            mx.zeroize();
+            bail!("no variant within {within} generates the target, try --edits 2 or --candidates");
+        };
+
+        let mut variant = mx.clone();
+        println!("Recovered the diagram by:");
+        for edit in chosen(i) {
+            println!("  {}", edit.describe(&mx));
+            edit.apply(&mut variant);
+        }
+        mx.zeroize();
+        println!(
+            "Fingerprint: {}",
+            fingerprint(&T::master(&variant, salt.as_bytes())?)
+        );
+        println!();
+        println!("Diagram: ");
+        println!("{}", variant.fmt_table(false));
+
+        let saved = match &self.save {
+            Some(path) => variant.to_file(path).map(|_| {
+                eprintln!(
+                    "Warning: diagram is saved in plaintext to `{path}`, anyone with it and the password can restore the wallets."
+                )
+            }),
+            None => Ok(()),
+        };
+        variant.zeroize();
+        saved
+    }
+
+    /// Candidate cell values, one per line, with character forms decoded.
+    fn candidates<T: VariantCell>(&self) -> anyhow::Result<Vec<T>> {
+        let Some(path) = &self.candidates else {
+            return Ok(vec![]);
+        };
+        let text = SecretString::new(std::fs::read_to_string(path)?);
+        let mut values: Vec<T> = vec![];
+        for (i, line) in text.as_str().lines().enumerate() {
+            let decoded = SecretString::new(unicode_decode_forms(line));
+            if decoded.as_str().is_empty() {
+                continue;
+            }
+            let value = T::from_text(decoded.as_str())
+                .map_err(|msg| anyhow!("invalid candidates `{path}`: line {}: {msg}", i + 1))?;
+            if !values.contains(&value) {
+                values.push(value);
+            }
+        }
+        Ok(values)
+    }
+
+    fn target(&self) -> anyhow::Result<Target> {
+        let target = &self.target;
+        if let Some(fp) = target.fingerprint {
+            return Ok(Target::Fingerprint(fp));
+        }
+        if let Some(addr) = &target.address {
+            return Ok(Target::Address(addr.trim().to_owned()));
+        }
+        let words = target.mnemonic.as_ref().ok_or(anyhow!("missing target"))?;
+        let words = nfkd_secret(words);
+        let mnemonic = Mnemonic::from_str(words.as_str())?;
+        let words = SecretString::new(mnemonic.to_string());
+        Ok(Target::Mnemonic(mnemonic, words))
+    }
+}
+
+/// Phrases listed without an address or xpub to check them by
+const LIST_MAX: u64 = 100_000_000;
+
+impl crate::Execute for RecoverMnemonicCommand {
+    fn execute(&mut self) -> anyhow::Result<()> {
+        let text = self.phrase_text()?;
+        let (phrase, notes) = Phrase::parse(text.as_str(), self.language, self.swapped)?;
+        for note in notes {
+            eprintln!("Note: {note}");
+        }
+        let total = phrase
+            .total()
+            .ok_or(anyhow!("too many phrases to search, fill in more words"))?;
+        let valid = total as f64 * phrase.checksum_share();
+        eprintln!(
+            "Search space: {total} phrases of the {} wordlist, about {valid:.0} with a valid checksum",
+            phrase.language
+        );
+
+        match self.wallet_key()? {
+            Some(key) => self.search(&phrase, total, &key),
+            None => self.list(&phrase, total),
+        }
+    }
+}
+
+/// Wallet the mnemonic generated, to recognize it by
+enum WalletKey {
+    Address(String),
+    /// Extended public key without its version bytes
+    Xpub(Vec<u8>),
+}
+
+impl WalletKey {
+    /// Path of the master key by which the wallet is derived, if any.
+    fn find(&self, master: &Xpriv, account: u32, amount: u32) -> anyhow::Result<Option<String>> {
+        for derive in DerivePath::all() {
+            match self {
+                WalletKey::Address(addr) => {
+                    for index in 0..amount {
+                        let (derived, pk) = derive.wallet(master, account, index)?;
+                        drop(SecretString::new(pk));
+                        if derived == *addr {
+                            return Ok(Some(format!("{}/0/{index}", derive.path(account))));
+                        }
+                    }
+                }
+                WalletKey::Xpub(key) => {
+                    let (xpub, xprv) = derive.account(master, account)?;
+                    drop(SecretString::new(xprv));
+                    if extended_key(&xpub)? == *key {
+                        return Ok(Some(derive.path(account)));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Key data of an extended key in any version: xpub, ypub or zpub.
+fn extended_key(s: &str) -> anyhow::Result<Vec<u8>> {
+    match base58::decode_check(s) {
+        Ok(data) if data.len() == 78 => Ok(data[4..].to_vec()),
+        _ => Err(anyhow!("invalid extended public key `{s}`")),
+    }
+}
+
+impl RecoverMnemonicCommand {
+    fn wallet_key(&self) -> anyhow::Result<Option<WalletKey>> {
+        match (&self.target.address, &self.target.xpub) {
+            (Some(addr), _) => Ok(Some(WalletKey::Address(addr.trim().to_owned()))),
+            (None, Some(xpub)) => Ok(Some(WalletKey::Xpub(extended_key(xpub.trim())?))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// List the phrases with a valid checksum.
+    fn list(&self, phrase: &Phrase, total: u64) -> anyhow::Result<()> {
+        if total > LIST_MAX {
+            bail!("too many phrases to list, check them by --address or --xpub");
+        }
+        // swapped words may repeat a phrase
+        let mut seen = std::collections::HashSet::new();
+        let mut count = 0;
+        for n in 0..total {
+            let indices = phrase.indices(n);
+            let Some(words) = phrase.valid_phrase(&indices) else {
+                continue;
+            };
+            if self.swapped && !seen.insert(indices) {
+                continue;
+            }
+            count += 1;
+            if count <= self.limit {
+                println!("{}", words.as_str());
+            }
+        }
+        match count {
+            0 => bail!("no phrase has a valid checksum"),
+            n if n > self.limit => eprintln!(
+                "Listed {} of {n} phrases with a valid checksum, check them by --address or --xpub",
+                self.limit
+            ),
+            n => eprintln!("{n} phrases with a valid checksum"),
+        }
+        Ok(())
+    }
+
+    /// Search the phrase generating the wallet.
+    fn search(&self, phrase: &Phrase, total: u64, key: &WalletKey) -> anyhow::Result<()> {
+        let passphrase = self.passphrase()?;
+        let check = |n: u64| -> anyhow::Result<Option<(SecretString, String)>> {
+            let Some(words) = phrase.valid_phrase(&phrase.indices(n)) else {
+                return Ok(None);
+            };
+            // a phrase of words common to two wordlists is ambiguous, and not the one
+            let Ok(master) = Xpriv::from_mnemonic(words.as_str(), passphrase.as_str()) else {
+                return Ok(None);
+            };
+            let master = SecretXpriv::new(master);
+            Ok(key
+                .find(&master, self.account, self.amount)?
+                .map(|path| (words, path)))
+        };
+
+        // the first valid phrase gives the estimate
+        let threads = threads(self.threads);
+        if let Some(n) = (0..total).find(|&n| phrase.valid_phrase(&phrase.indices(n)).is_some()) {
+            let start = Instant::now();
+            check(n)?;
+            let each = start.elapsed().as_secs_f64();
+            eprintln!(
+                "Checking them on {threads} threads, about {}",
+                duration_text(each * total as f64 * phrase.checksum_share() / threads as f64)
+            );
+        }
+
+        let found = parallel_search(total, threads, check)?;
+        let Some((words, path)) = found else {
+            bail!("no phrase generates the wallet, check the passphrase, --account and --amount");
+        };
+        println!("Recovered mnemonic: {}", words.as_str());
+        println!("Wallet path: {path}");
+        Ok(())
+    }
+}
+
+/// Check of a candidate password, lines to print if it is the one
+type PasswordCheck<'a> = Box<dyn Fn(&str) -> anyhow::Result<Option<Vec<String>>> + Sync + 'a>;
+
+impl crate::Execute for RecoverPasswordCommand {
+    fn execute(&mut self) -> anyhow::Result<()> {
+        let text = SecretString::new(std::fs::read_to_string(&self.wordlist)?);
+        let words: Vec<SecretString> = text
+            .as_str()
+            .lines()
+            .map(|ln| ln.trim_end_matches('\r'))
+            .filter(|ln| !ln.is_empty())
+            .map(|ln| SecretString::new(ln.to_owned()))
+            .collect();
+        let candidates = Candidates::new(words, &self.rules);
+        if candidates.total() == 0 {
+            bail!("no password in wordlist `{}`", self.wordlist);
+        }
+
+        let check: PasswordCheck = match &self.source {
+            Some(PasswordSource::Diagram(path)) => match self.complex {
+                false => self.diagram_check::<char>(path)?,
+                true => self.diagram_check::<String>(path)?,
+            },
+            Some(PasswordSource::Mnemonic(words)) => self.mnemonic_check(words)?,
+            None => {
+                let key = self.target.bip38.clone().ok_or(anyhow!("missing target"))?;
+                // a wrong password fails the address check of the key
+                Box::new(move |pwd| {
+                    Ok(key.bip38_decrypt(pwd).ok().map(|pk| {
+                        drop(SecretString::new(pk));
+                        vec![]
+                    }))
+                })
+            }
+        };
+
+        let checkpoint = self.checkpoint.as_ref().map(|path| {
+            let target = format!("{:?}", self.target);
+            let options = format!(
+                "{:?} {} {} {}",
+                self.rules, self.index, self.amount, self.account
+            );
+            Checkpoint::new(
+                path,
+                &[text.as_bytes(), target.as_bytes(), options.as_bytes()],
+            )
+        });
+        self.search(&candidates, &check, checkpoint.as_ref())
+    }
+}
+
+impl RecoverPasswordCommand {
+    /// Check of a diagram's salt password against the fingerprint or a BIP85 address.
+    fn diagram_check<T: VariantCell + 'static>(
+        &self,
+        path: &str,
+    ) -> anyhow::Result<PasswordCheck<'_>> {
+        let mx = Zeroizing::new(Matrix::<T, 7, 7>::from_file(path)?);
+        let target = match (self.target.fingerprint, &self.target.address) {
+            (Some(fp), _) => Target::Fingerprint(fp),
+            (None, Some(addr)) => Target::Address(addr.trim().to_owned()),
+            (None, None) => bail!("a diagram is checked by --fingerprint or --address"),
+        };
+        let indices = self.index..self.index + self.amount;
+        Ok(Box::new(move |pwd| {
+            let master = T::master(&mx, nfc_secret(pwd).as_bytes())?;
+            Ok(target
+                .matches(&master, &indices)?
+                .then(|| vec![format!("Fingerprint: {}", fingerprint(&master))]))
+        }))
+    }
+
+    /// Check of a mnemonic's BIP39 passphrase against the fingerprint or an address.
+    fn mnemonic_check(&self, words: &SecretString) -> anyhow::Result<PasswordCheck<'_>> {
+        let mnemonic = Mnemonic::from_str(nfkd_secret(words).as_str())?;
+        let words = SecretString::new(mnemonic.to_string());
+        let secp = Secp256k1::signing_only();
+        match (self.target.fingerprint, &self.target.address) {
+            (Some(fp), _) => Ok(Box::new(move |pwd| {
+                let passphrase = nfkd_secret(pwd);
+                let master =
+                    SecretXpriv::new(Xpriv::from_mnemonic(words.as_str(), passphrase.as_str())?);
+                Ok((master.fingerprint(&secp) == fp).then(Vec::new))
+            })),
+            (None, Some(addr)) => {
+                let key = WalletKey::Address(addr.trim().to_owned());
+                Ok(Box::new(move |pwd| {
+                    let passphrase = nfkd_secret(pwd);
+                    let master = SecretXpriv::new(Xpriv::from_mnemonic(
+                        words.as_str(),
+                        passphrase.as_str(),
+                    )?);
+                    Ok(key
+                        .find(&master, self.account, self.amount)?
+                        .map(|path| vec![format!("Wallet path: {path}")]))
+                }))
+            }
+            (None, None) => bail!("a mnemonic is checked by --fingerprint or --address"),
+        }
+    }
+
+    /// Search the candidates, resuming from and saving to the checkpoint.
+    fn search(
+        &self,
+        candidates: &Candidates,
+        check: &PasswordCheck,
+        checkpoint: Option<&Checkpoint>,
+    ) -> anyhow::Result<()> {
+        let total = candidates.total();
+        let start = match checkpoint {
+            Some(checkpoint) => checkpoint.load()?,
+            None => 0,
+        };
+        if start >= total {
+            bail!("all {total} candidates are checked by the checkpoint, delete it to start over");
+        }
+
+        // the first candidate's time gives the estimate
+        let threads = threads(self.threads);
+        let timer = Instant::now();
+        if let Some(lines) = check(candidates.get(start).as_str())? {
+            return found(&candidates.get(start), lines, checkpoint);
+        }
+        let each = timer.elapsed().as_secs_f64();
+        let resumed = match start {
+            0 => String::new(),
+            n => format!(", resumed after {n}"),
+        };
+        eprintln!(
+            "Searching {total} candidate passwords{resumed} on {threads} threads, about {}",
+            duration_text(each * (total - start) as f64 / threads as f64)
+        );
+
+        let result = parallel_search_from(
+            start + 1,
+            total,
+            threads,
+            |n| {
+                let pwd = candidates.get(n);
+                Ok(check(pwd.as_str())?.map(|lines| (pwd, lines)))
+            },
+            |checked| match checkpoint {
+                Some(checkpoint) => checkpoint.save(checked),
+                None => Ok(()),
+            },
+        )?;
+        let Some((pwd, lines)) = result else {
+            bail!("no candidate password generates the target, try more --rules or words");
+        };
+        found(&pwd, lines, checkpoint)
+    }
+}
+
+/// Print the recovered password, the checkpoint of the search is done with.
+fn found(
+    pwd: &SecretString,
+    lines: Vec<String>,
+    checkpoint: Option<&Checkpoint>,
+) -> anyhow::Result<()> {
+    println!("Recovered password: {}", pwd.as_str());
+    if pwd.as_str().chars().any(|c| !(' '..='~').contains(&c)) {
+        println!("Typed as: {}", unicode_encode(pwd.as_str()));
+    }
+    if SecretString::new(unicode_decode(pwd.as_str())).as_str() != pwd.as_str() {
+        eprintln!(
+            "Note: character forms of the password are taken as typed, a prompt would decode them"
+        );
+    }
+    for line in lines {
+        println!("{line}");
+    }
+    checkpoint.map_or(Ok(()), Checkpoint::remove)
+}
+
+/// Threads to search on, all cores by default.
+fn threads(threads: Option<usize>) -> usize {
+    threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, usize::from))
+        .max(1)
+}
@@ -0,0 +1,8 @@
+mod arg;
+mod checkpoint;
+mod execute;
+mod mutation;
+mod phrase;
+mod variant;
+
+pub use arg::{RecoverDiagramCommand, RecoverMnemonicCommand, RecoverPasswordCommand};
@@ -0,0 +1,188 @@
+//! Candidate passwords of a wordlist and mutation rules.
+
+use super::arg::Rule;
+use crate::utils::{SecretString, unicode_decode, unicode_decode_forms, unicode_encode};
+use clap::ValueEnum;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Lookalikes of letters in leetspeak
+const LEET: &[(char, &str)] = &[
+    ('a', "4@"),
+    ('b', "8"),
+    ('e', "3"),
+    ('g', "9"),
+    ('i', "1!"),
+    ('l', "1"),
+    ('o', "0"),
+    ('s', "5$"),
+    ('t', "7"),
+];
+
+/// Letters of a password replaced in leetspeak at most, the later ones are kept
+const LEET_LETTERS: usize = 8;
+
+/// Candidates of all base passwords, numbered word by word.
+pub struct Candidates {
+    words: Vec<SecretString>,
+    rules: Vec<Rule>,
+    /// Number of the first candidate of each word
+    starts: Vec<u64>,
+    total: u64,
+    /// Tells the candidates of this list apart in the thread cache
+    id: u64,
+}
+
+thread_local! {
+    /// Candidates of the word a thread checks, as candidates are taken in order
+    static CACHE: RefCell<Option<(u64, usize, Vec<SecretString>)>> = const { RefCell::new(None) };
+}
+
+impl Candidates {
+    /// Candidates of base passwords, the rules applied as forms, leet, case, digits.
+    pub fn new(words: Vec<SecretString>, rules: &[Rule]) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let rules: Vec<Rule> = Rule::value_variants()
+            .iter()
+            .filter(|r| rules.contains(r))
+            .copied()
+            .collect();
+        let mut starts = vec![];
+        let mut total = 0;
+        for word in &words {
+            starts.push(total);
+            total += mutate(word.as_str(), &rules).len() as u64;
+        }
+        Self {
+            words,
+            rules,
+            starts,
+            total,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// The `n`th candidate.
+    pub fn get(&self, n: u64) -> SecretString {
+        let word = self.starts.partition_point(|&s| s <= n) - 1;
+        let index = (n - self.starts[word]) as usize;
+        CACHE.with_borrow_mut(|cache| {
+            if !matches!(cache, Some((id, w, _)) if *id == self.id && *w == word) {
+                let variants = mutate(self.words[word].as_str(), &self.rules);
+                *cache = Some((self.id, word, variants));
+            }
+            let (_, _, variants) = cache.as_ref().expect("cached");
+            variants[index].clone()
+        })
+    }
+}
+
+/// Distinct variants of a password by the rules, the password as is first.
+pub fn mutate(word: &str, rules: &[Rule]) -> Vec<SecretString> {
+    let mut variants = vec![SecretString::new(word.to_owned())];
+    for rule in rules {
+        let mut next: Vec<SecretString> = vec![];
+        for v in &variants {
+            for m in apply(*rule, v.as_str()) {
+                if !next.iter().any(|n| n.as_str() == m.as_str()) {
+                    next.push(m);
+                }
+            }
+        }
+        variants = next;
+    }
+    variants
+}
+
+fn apply(rule: Rule, word: &str) -> Vec<SecretString> {
+    let texts = match rule {
+        Rule::Forms => vec![
+            word.to_owned(),
+            unicode_decode(word),
+            unicode_decode_forms(word),
+            unicode_encode(word),
+        ],
+        Rule::Leet => leet(word),
+        Rule::Case => {
+            let lower = word.to_lowercase();
+            let mut chars = lower.chars();
+            let capitalized = chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect())
+                .unwrap_or_default();
+            vec![word.to_owned(), lower, word.to_uppercase(), capitalized]
+        }
+        Rule::Digits => {
+            let mut texts = vec![word.to_owned()];
+            texts.extend((0..10).map(|d| format!("{word}{d}")));
+            texts.extend((0..100).map(|d| format!("{word}{d:02}")));
+            texts
+        }
+    };
+    texts.into_iter().map(SecretString::new).collect()
+}
+
+/// Every choice of kept or replaced letters, up to `LEET_LETTERS` of them.
+fn leet(word: &str) -> Vec<String> {
+    let mut texts = vec![String::new()];
+    let mut letters = 0;
+    for ch in word.chars() {
+        let subs = match LEET.iter().find(|(c, _)| ch.to_ascii_lowercase() == *c) {
+            Some((_, subs)) if letters < LEET_LETTERS => {
+                letters += 1;
+                subs
+            }
+            _ => "",
+        };
+        texts = texts
+            .iter()
+            .flat_map(|t| {
+                std::iter::once(ch)
+                    .chain(subs.chars())
+                    .map(move |c| format!("{t}{c}"))
+            })
+            .collect();
+    }
+    texts
+}
+
+#[cfg(test)]
+mod mutation_test {
+    use super::*;
+
+    fn texts(variants: Vec<SecretString>) -> Vec<String> {
+        variants.iter().map(|v| v.as_str().to_owned()).collect()
+    }
+
+    #[test]
+    fn test_mutate() {
+        assert_eq!(texts(mutate("bob", &[Rule::Case])), ["bob", "BOB", "Bob"]);
+        let leet = texts(mutate("sat", &[Rule::Leet]));
+        assert_eq!(leet.len(), 3 * 3 * 2);
+        assert!(leet.contains(&"$47".to_owned()));
+        let forms = texts(mutate("caf\\u{e9}", &[Rule::Forms]));
+        assert_eq!(forms, ["caf\\u{e9}", "café"]);
+
+        let digits = texts(mutate("abc", &[Rule::Case, Rule::Digits]));
+        assert_eq!(digits.len(), 3 * 111);
+        assert!(digits.contains(&"Abc07".to_owned()));
+        assert!(digits.contains(&"ABC9".to_owned()));
+    }
+
+    #[test]
+    fn test_candidates() {
+        let words = ["one", "Two"].map(|w| SecretString::new(w.to_owned()));
+        let list = Candidates::new(words.to_vec(), &[Rule::Digits, Rule::Case]);
+        // a lowercase word is its own lowercase form
+        assert_eq!(list.total(), 2 * 3 * 111);
+        assert_eq!(list.get(0).as_str(), "one");
+        assert_eq!(list.get(333).as_str(), "Two");
+        // case applies before digits whatever the listed order
+        assert_eq!(list.get(111).as_str(), "ONE");
+        assert_eq!(list.get(2 * 3 * 111 - 1).as_str(), "TWO99");
+    }
+}
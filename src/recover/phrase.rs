@@ -0,0 +1,241 @@
+//! Candidate phrases of a partly remembered mnemonic.
+
+use crate::utils::{SecretString, nfkd_secret};
+use anyhow::{anyhow, bail};
+use artimonist::{Language, Mnemonic};
+
+/// Misspelled words are replaced by wordlist words within this edit distance
+const MAX_DISTANCE: usize = 2;
+
+/// Words of a phrase as remembered
+pub struct Phrase {
+    pub language: Language,
+    /// Candidate word indices of each position
+    slots: Vec<Vec<usize>>,
+    /// Positions of two words swapped, the phrase in order first
+    swaps: Vec<Option<(usize, usize)>>,
+}
+
+impl Phrase {
+    /// Parse a phrase of words, `?` for a missing word and `word?` for an uncertain one.
+    /// Words not in the wordlist are replaced by the nearest ones, noted in the result.
+    pub fn parse(
+        text: &str,
+        language: Option<Language>,
+        swapped: bool,
+    ) -> anyhow::Result<(Self, Vec<String>)> {
+        let text = nfkd_secret(&text.to_lowercase());
+        let tokens: Vec<&str> = text.as_str().split_whitespace().collect();
+        if !matches!(tokens.len(), 12 | 15 | 18 | 21 | 24) {
+            bail!(
+                "a mnemonic has 12, 15, 18, 21 or 24 words, found {}",
+                tokens.len()
+            );
+        }
+        let language = match language {
+            Some(language) => language,
+            None => detect_language(&tokens)?,
+        };
+
+        let mut notes = vec![];
+        let mut slots = vec![];
+        for (i, &token) in tokens.iter().enumerate() {
+            let (word, uncertain) = match token.strip_suffix('?') {
+                Some(word) => (word, true),
+                None => (token, false),
+            };
+            let slot = match (word, language.index_of(word)) {
+                ("", _) => (0..2048).collect(),
+                (_, Some(index)) if !uncertain => vec![index],
+                _ => {
+                    let near = nearest_words(language, word);
+                    let listed: Vec<&str> =
+                        near.iter().filter_map(|&w| language.word_at(w)).collect();
+                    notes.push(format!(
+                        "word {} `{word}` may be: {}",
+                        i + 1,
+                        listed.join(", ")
+                    ));
+                    near
+                }
+            };
+            slots.push(slot);
+        }
+
+        let mut swaps = vec![None];
+        if swapped {
+            for i in 0..slots.len() {
+                for j in i + 1..slots.len() {
+                    if slots[i] != slots[j] {
+                        swaps.push(Some((i, j)));
+                    }
+                }
+            }
+        }
+        Ok((
+            Self {
+                language,
+                slots,
+                swaps,
+            },
+            notes,
+        ))
+    }
+
+    /// Count of phrases to check, `None` beyond `u64`.
+    pub fn total(&self) -> Option<u64> {
+        self.slots
+            .iter()
+            .try_fold(self.swaps.len() as u64, |n, slot| {
+                n.checked_mul(slot.len() as u64)
+            })
+    }
+
+    /// Share of the phrases with a valid checksum: 4 bits of 12 words, 8 bits of 24 words.
+    pub fn checksum_share(&self) -> f64 {
+        1.0 / (1u64 << (self.slots.len() / 3)) as f64
+    }
+
+    /// Word indices of the `n`th phrase, the last word changing fastest.
+    pub fn indices(&self, mut n: u64) -> Vec<usize> {
+        let mut indices = vec![0; self.slots.len()];
+        for (i, slot) in self.slots.iter().enumerate().rev() {
+            indices[i] = slot[(n % slot.len() as u64) as usize];
+            n /= slot.len() as u64;
+        }
+        if let Some((a, b)) = self.swaps[n as usize] {
+            indices.swap(a, b);
+        }
+        indices
+    }
+
+    /// Phrase of word indices if its checksum is valid.
+    pub fn valid_phrase(&self, indices: &[usize]) -> Option<SecretString> {
+        Mnemonic::verify_checksum(indices).ok()?;
+        let words: Vec<&str> = indices
+            .iter()
+            .filter_map(|&i| self.language.word_at(i))
+            .collect();
+        Some(SecretString::new(words.join(" ")))
+    }
+}
+
+/// The language with the most words of the phrase.
+fn detect_language(tokens: &[&str]) -> anyhow::Result<Language> {
+    let counts: Vec<(Language, usize)> = Language::all()
+        .iter()
+        .map(|&lang| {
+            let count = tokens
+                .iter()
+                .filter(|t| lang.index_of(t.trim_end_matches('?')).is_some())
+                .count();
+            (lang, count)
+        })
+        .collect();
+    let max = counts.iter().map(|&(_, n)| n).max().unwrap_or(0);
+    let mut best = counts.iter().filter(|&&(_, n)| n == max && n > 0);
+    match (best.next(), best.next()) {
+        (Some(&(lang, _)), None) => Ok(lang),
+        (Some(_), Some(_)) => Err(anyhow!(
+            "the words fit several languages, choose one with --language"
+        )),
+        _ => Err(anyhow!(
+            "no word is in a wordlist, choose the language with --language"
+        )),
+    }
+}
+
+/// Words within `MAX_DISTANCE` of a misspelled word, or else the nearest ones,
+/// and the word of its first 4 letters: those are unique in most wordlists.
+fn nearest_words(language: Language, word: &str) -> Vec<usize> {
+    let distances: Vec<(usize, usize)> = language
+        .wordlist()
+        .enumerate()
+        .map(|(i, w)| (i, edit_distance(word, w)))
+        .collect();
+    let min = distances.iter().map(|&(_, d)| d).min().unwrap_or(0);
+    let prefix: String = word.chars().take(4).collect();
+    let mut near: Vec<(usize, usize)> = distances
+        .into_iter()
+        .filter(|&(i, d)| {
+            let prefixed = prefix.chars().count() == 4
+                && language.word_at(i).is_some_and(|w| w.starts_with(&prefix));
+            d <= MAX_DISTANCE.max(min) || prefixed
+        })
+        .collect();
+    near.sort_by_key(|&(_, d)| d);
+    near.into_iter().map(|(i, _)| i).collect()
+}
+
+/// Edits between words: inserted, deleted, substituted or adjacent swapped characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod phrase_test {
+    use super::*;
+
+    const MNEMONIC: &str = "lake album jump occur hedgehog fantasy drama sauce oyster velvet gadget control behave hamster begin";
+
+    #[test]
+    fn test_phrase() {
+        let (phrase, notes) = Phrase::parse(MNEMONIC, None, false).unwrap();
+        assert_eq!(phrase.language, Language::English);
+        assert_eq!(phrase.total(), Some(1));
+        assert!(notes.is_empty());
+
+        // missing and misspelled words
+        let text = MNEMONIC.replace("jump", "?").replace("velvet", "vlevet");
+        let (phrase, notes) = Phrase::parse(&text, None, false).unwrap();
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].starts_with("word 10 `vlevet` may be: velvet"));
+        let found: Vec<String> = (0..phrase.total().unwrap())
+            .filter_map(|n| phrase.valid_phrase(&phrase.indices(n)))
+            .map(|p| p.as_str().to_owned())
+            .collect();
+        assert!(found.contains(&MNEMONIC.to_owned()));
+        // 15 words have 5 checksum bits
+        assert!(found.len() < 2 * phrase.total().unwrap() as usize / 32);
+
+        // swapped words
+        let text = MNEMONIC.replace("lake album", "album lake");
+        let (phrase, _) = Phrase::parse(&text, None, true).unwrap();
+        assert_eq!(phrase.total(), Some(1 + 15 * 14 / 2));
+        let found = (0..phrase.total().unwrap())
+            .filter_map(|n| phrase.valid_phrase(&phrase.indices(n)))
+            .any(|p| p.as_str() == MNEMONIC);
+        assert!(found);
+
+        assert!(Phrase::parse("lake album jump", None, false).is_err());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("velvet", "vlevet"), 1);
+        assert_eq!(edit_distance("abandon", "abandn"), 1);
+        assert_eq!(edit_distance("abandon", "abnadon"), 1);
+        assert_eq!(edit_distance("", "ab"), 2);
+        assert!(nearest_words(Language::English, "abandn").contains(&0));
+        // first 4 letters name the word
+        let near = nearest_words(Language::English, "hedgehgo");
+        assert_eq!(Language::English.word_at(near[0]), Some("hedgehog"));
+    }
+}
@@ -0,0 +1,279 @@
+//! Nearby variants of a remembered diagram.
+
+use crate::diagram::DiagramCell;
+use crate::utils::unicode_encode;
+use artimonist::{ComplexDiagram, GenericDiagram, Matrix, SimpleDiagram, Xpriv};
+use zeroize::Zeroize;
+
+/// Groups of characters often mistaken for each other
+const CONFUSABLES: &[&str] = &[
+    "0Oo",
+    "1lI|i",
+    "2Zz",
+    "5Ss",
+    "6Gb",
+    "8B",
+    "9gq",
+    "uvʋ",
+    "-–—‐",
+    "'‘’`´",
+    "\"“”″",
+    ".·。",
+    ",，、",
+    "xX×",
+    "~～",
+    ":：",
+    "¥￥",
+    "$＄",
+];
+
+type Pos = (usize, usize);
+
+/// Cell of a diagram to search variants of.
+pub trait VariantCell: DiagramCell + Clone + PartialEq + ToString + Zeroize + Send + Sync {
+    /// Master key of a diagram of these cells
+    fn master(mx: &Matrix<Self, 7, 7>, salt: &[u8]) -> anyhow::Result<Xpriv>;
+    /// Likely mistypes of the cell: confusable characters, case and typos
+    fn alterations(&self) -> Vec<Self>;
+}
+
+impl VariantCell for char {
+    fn master(mx: &Matrix<Self, 7, 7>, salt: &[u8]) -> anyhow::Result<Xpriv> {
+        let mut diagram = SimpleDiagram(*mx);
+        let master = diagram.bip32_master(salt);
+        diagram.0.zeroize();
+        Ok(master?)
+    }
+
+    fn alterations(&self) -> Vec<Self> {
+        confusables(*self)
+    }
+}
+
+impl VariantCell for String {
+    fn master(mx: &Matrix<Self, 7, 7>, salt: &[u8]) -> anyhow::Result<Xpriv> {
+        let mut diagram = ComplexDiagram(mx.clone());
+        let master = diagram.bip32_master(salt);
+        diagram.0.zeroize();
+        Ok(master?)
+    }
+
+    fn alterations(&self) -> Vec<Self> {
+        let chars: Vec<char> = self.chars().collect();
+        let mut texts: Vec<String> = vec![];
+        for (i, &ch) in chars.iter().enumerate() {
+            let (head, tail) = (&chars[..i], &chars[i + 1..]);
+            // substituted character
+            for sub in confusables(ch) {
+                texts.push(head.iter().chain([&sub]).chain(tail).collect());
+            }
+            // missing character
+            texts.push(head.iter().chain(tail).collect());
+            // adjacent characters swapped
+            if let Some(&next) = tail.first() {
+                texts.push(head.iter().chain([&next, &ch]).chain(&tail[1..]).collect());
+            }
+        }
+        let mut values: Vec<String> = vec![];
+        for text in texts {
+            if let Ok(v) = String::from_text(&text)
+                && v != *self
+                && !values.contains(&v)
+            {
+                values.push(v);
+            }
+        }
+        values
+    }
+}
+
+/// Confusables and the other case of a character.
+fn confusables(ch: char) -> Vec<char> {
+    let mut chars: Vec<char> = CONFUSABLES
+        .iter()
+        .filter(|group| group.contains(ch))
+        .flat_map(|group| group.chars())
+        .chain(ch.to_lowercase())
+        .chain(ch.to_uppercase())
+        .collect();
+    // fullwidth forms typed by an input method
+    if let Some(c) = match ch as u32 {
+        0x21..=0x7e => char::from_u32(ch as u32 + 0xfee0),
+        0xff01..=0xff5e => char::from_u32(ch as u32 - 0xfee0),
+        _ => None,
+    } {
+        chars.push(c);
+    }
+    chars.retain(|&c| c != ch);
+    chars.sort_unstable();
+    chars.dedup();
+    chars
+}
+
+/// Change of one or two cells.
+#[derive(Debug, Clone)]
+pub enum Edit<T> {
+    /// A cell was typed differently
+    Alter(Pos, T),
+    /// A cell was left out
+    Fill(Pos, T),
+    /// A cell was added by mistake
+    Empty(Pos),
+    /// Two cells were swapped, or a cell was moved into an empty one
+    Swap(Pos, Pos),
+}
+
+impl<T: VariantCell> Edit<T> {
+    /// Single edits of a diagram, likely ones first.
+    pub fn all(mx: &Matrix<T, 7, 7>, candidates: &[T]) -> Vec<Self> {
+        let positions: Vec<Pos> = (0..49).map(|i| (i / 7, i % 7)).collect();
+        let filled = |&(r, c): &Pos| mx[r][c].is_some();
+
+        let mut edits = vec![];
+        for p @ &(r, c) in positions.iter().filter(|p| filled(p)) {
+            let value = mx[r][c].as_ref().expect("filled");
+            for v in value.alterations() {
+                edits.push(Edit::Alter(*p, v));
+            }
+        }
+        for p @ &(r, c) in positions.iter().filter(|p| filled(p)) {
+            for v in candidates.iter().filter(|&v| Some(v) != mx[r][c].as_ref()) {
+                edits.push(Edit::Alter(*p, v.clone()));
+            }
+        }
+        for p in positions.iter().filter(|p| filled(p)) {
+            edits.push(Edit::Empty(*p));
+        }
+        for (i, a) in positions.iter().enumerate() {
+            for b in &positions[i + 1..] {
+                let (va, vb) = (&mx[a.0][a.1], &mx[b.0][b.1]);
+                if (va.is_some() || vb.is_some()) && va != vb {
+                    edits.push(Edit::Swap(*a, *b));
+                }
+            }
+        }
+        for p in positions.iter().filter(|p| !filled(p)) {
+            for v in candidates {
+                edits.push(Edit::Fill(*p, v.clone()));
+            }
+        }
+        edits
+    }
+
+    /// Cells changed by the edit.
+    pub fn cells(&self) -> Vec<Pos> {
+        match self {
+            Edit::Alter(p, _) | Edit::Fill(p, _) | Edit::Empty(p) => vec![*p],
+            Edit::Swap(a, b) => vec![*a, *b],
+        }
+    }
+
+    /// Whether the edits change different cells.
+    pub fn disjoint(&self, other: &Self) -> bool {
+        let cells = other.cells();
+        self.cells().iter().all(|p| !cells.contains(p))
+    }
+
+    pub fn apply(&self, mx: &mut Matrix<T, 7, 7>) {
+        match self {
+            Edit::Alter((r, c), v) | Edit::Fill((r, c), v) => mx[*r][*c] = Some(v.clone()),
+            Edit::Empty((r, c)) => mx[*r][*c] = None,
+            Edit::Swap((r1, c1), (r2, c2)) => {
+                let a = mx[*r1][*c1].take();
+                mx[*r1][*c1] = std::mem::replace(&mut mx[*r2][*c2], a);
+            }
+        }
+    }
+
+    /// Edit as a change of the remembered diagram, like ``cell 1,7 `O` -> `0` ``.
+    pub fn describe(&self, mx: &Matrix<T, 7, 7>) -> String {
+        let text = |(r, c): Pos| match &mx[r][c] {
+            Some(v) => format!("`{}`", unicode_encode(&v.to_text())),
+            None => "empty".to_owned(),
+        };
+        match self {
+            Edit::Alter(p, v) => format!(
+                "cell {},{} {} -> `{}`",
+                p.0 + 1,
+                p.1 + 1,
+                text(*p),
+                unicode_encode(&v.to_text())
+            ),
+            Edit::Fill(p, v) => format!(
+                "cell {},{} filled with `{}`",
+                p.0 + 1,
+                p.1 + 1,
+                unicode_encode(&v.to_text())
+            ),
+            Edit::Empty(p) => format!("cell {},{} {} emptied", p.0 + 1, p.1 + 1, text(*p)),
+            Edit::Swap(a, b) if mx[b.0][b.1].is_none() => {
+                format!(
+                    "cell {},{} moved to {},{}",
+                    a.0 + 1,
+                    a.1 + 1,
+                    b.0 + 1,
+                    b.1 + 1
+                )
+            }
+            Edit::Swap(a, b) if mx[a.0][a.1].is_none() => {
+                format!(
+                    "cell {},{} moved to {},{}",
+                    b.0 + 1,
+                    b.1 + 1,
+                    a.0 + 1,
+                    a.1 + 1
+                )
+            }
+            Edit::Swap(a, b) => format!(
+                "cells {},{} and {},{} swapped",
+                a.0 + 1,
+                a.1 + 1,
+                b.0 + 1,
+                b.1 + 1
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod variant_test {
+    use super::*;
+
+    #[test]
+    fn test_alterations() {
+        assert_eq!('O'.alterations(), ['0', 'o', 'Ｏ']);
+        assert!('l'.alterations().contains(&'1'));
+
+        let values = "Ab".to_owned().alterations();
+        for v in ["ab", "AB", "b", "A", "bA", "Ａb", "A6", "Aｂ"] {
+            assert!(values.contains(&v.to_owned()), "{v}");
+        }
+        assert!(!values.contains(&"Ab".to_owned()));
+    }
+
+    #[test]
+    fn test_edits() {
+        let mut mx: Matrix<char, 7, 7> = [[None; 7]; 7];
+        mx[0][6] = Some('O');
+        mx[1][1] = Some('x');
+
+        let edits = Edit::all(&mx, &['₿']);
+        let alters = 'O'.alterations().len() + 'x'.alterations().len();
+        // alterations, candidates, empties, swaps and moves, fills
+        assert_eq!(edits.len(), alters + 2 + 2 + (1 + 47 * 2) + 47);
+        assert!(matches!(edits[0], Edit::Alter((0, 6), '0')));
+
+        let mut variant = mx;
+        edits[0].apply(&mut variant);
+        assert_eq!(variant[0][6], Some('0'));
+        assert_eq!(edits[0].describe(&mx), "cell 1,7 `O` -> `0`");
+
+        let swap = Edit::<char>::Swap((0, 6), (6, 6));
+        let mut variant = mx;
+        swap.apply(&mut variant);
+        assert_eq!((variant[0][6], variant[6][6]), (None, Some('O')));
+        assert_eq!(swap.describe(&mx), "cell 1,7 moved to 7,7");
+        assert!(!swap.disjoint(&edits[0]));
+        assert!(swap.disjoint(&Edit::Empty((1, 1))));
+    }
+}
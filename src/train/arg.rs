@@ -0,0 +1,61 @@
+use crate::utils::{PasswordInput, SecretString};
+
+#[derive(clap::Parser, Debug)]
+pub struct TrainCommand {
+    #[command(subcommand)]
+    pub action: TrainAction,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum TrainAction {
+    /// Start a training record of a diagram, keeping no cell of it
+    New {
+        /// Training record file to create
+        #[clap(name = "RECORD")]
+        record: String,
+
+        /// Train a complex diagram
+        #[clap(long)]
+        complex: bool,
+
+        /// Read the diagram from text file instead of entering it twice
+        #[clap(long, value_name = "FILE")]
+        from: Option<String>,
+
+        /// Password of the diagram, which the record is bound to
+        #[clap(hide = true, long, env = "ARTIMONIST_PASSWORD", hide_env_values = true)]
+        password: Option<SecretString>,
+
+        /// Password input channels
+        #[clap(flatten)]
+        password_input: PasswordInput,
+    },
+    /// Recall the row remembered least, a chosen row or the full diagram
+    Quiz {
+        /// Training record file
+        #[clap(name = "RECORD")]
+        record: String,
+
+        /// Recall this row
+        #[clap(long, value_parser = clap::value_parser!(u8).range(1..=7))]
+        row: Option<u8>,
+
+        /// Recall the full diagram
+        #[clap(long, conflicts_with = "row")]
+        full: bool,
+
+        /// Password of the diagram, which the record is bound to
+        #[clap(hide = true, long, env = "ARTIMONIST_PASSWORD", hide_env_values = true)]
+        password: Option<SecretString>,
+
+        /// Password input channels
+        #[clap(flatten)]
+        password_input: PasswordInput,
+    },
+    /// Show the recall of each row over past quizzes
+    Stats {
+        /// Training record file
+        #[clap(name = "RECORD")]
+        record: String,
+    },
+}
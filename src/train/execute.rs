@@ -0,0 +1,186 @@
+use super::arg::{TrainAction, TrainCommand};
+use super::record::{Mark, Quiz, RECENT_QUIZZES, TrainRecord};
+use crate::diagram::{self, DiagramCell, LoadMatrix};
+use crate::utils::{
+    PasswordInput, SecretInput, SecretString, inquire_password, nfc_secret, require_input,
+};
+use artimonist::Matrix;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroize;
+
+impl crate::Execute for TrainCommand {
+    fn execute(&mut self) -> anyhow::Result<()> {
+        match &self.action {
+            TrainAction::New {
+                record,
+                complex,
+                from,
+                password,
+                password_input,
+            } => {
+                let password = || diagram_password(password.as_ref(), password_input);
+                match complex {
+                    false => new_record::<char>(record, from.as_deref(), password),
+                    true => new_record::<String>(record, from.as_deref(), password),
+                }
+            }
+            TrainAction::Quiz {
+                record,
+                row,
+                full,
+                password,
+                password_input,
+            } => {
+                let mut train = TrainRecord::from_file(record)?;
+                let asked: Vec<usize> = match (row, full) {
+                    (Some(r), _) => vec![*r as usize - 1],
+                    (None, true) => (0..7).collect(),
+                    (None, false) => {
+                        // the weakest row, a random one of equals to vary the practice
+                        let weakest = train.weakest_rows();
+                        let mut b = [0; 1];
+                        getrandom::getrandom(&mut b)?;
+                        vec![weakest[b[0] as usize % weakest.len()]]
+                    }
+                };
+                let password = || diagram_password(password.as_ref(), password_input);
+                match train.kind.as_str() {
+                    "simple" => quiz::<char>(&mut train, record, &asked, password)?,
+                    _ => quiz::<String>(&mut train, record, &asked, password)?,
+                }
+                println!();
+                print_stats(&train);
+                Ok(())
+            }
+            TrainAction::Stats { record } => {
+                print_stats(&TrainRecord::from_file(record)?);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Password of the diagram in NFC, as the diagram commands take it.
+fn diagram_password(
+    password: Option<&SecretString>,
+    input: &PasswordInput,
+) -> anyhow::Result<SecretString> {
+    let password = input.resolve(password, || inquire_password(true))?;
+    Ok(nfc_secret(&password))
+}
+
+/// Commit to a diagram and its password in a new record file, then wipe the cells.
+fn new_record<T>(
+    path: &str,
+    from: Option<&str>,
+    password: impl FnOnce() -> anyhow::Result<SecretString>,
+) -> anyhow::Result<()>
+where
+    T: DiagramCell + PartialEq + Zeroize,
+{
+    let mut mx = match from {
+        Some(file) => Matrix::<T, 7, 7>::from_file(file)?,
+        None => {
+            require_input("diagram", "--from")?;
+            // a typo would be memorized, enter it twice
+            Matrix::<T, 7, 7>::from_inquire(true)?
+        }
+    };
+    let record = password().and_then(|password| {
+        TrainRecord::commit(&mx, &password, scrypt::Params::RECOMMENDED_LOG_N)
+    });
+    mx.zeroize();
+    record?.to_file(path)?;
+
+    println!("Training record is created in `{path}`, it keeps no cell of the diagram.");
+    eprintln!(
+        "Warning: anyone with the record and the password can test guesses of the diagram row by row, keep it private."
+    );
+    Ok(())
+}
+
+/// Recall rows of the diagram, tell which are correct and record the quiz.
+fn quiz<T>(
+    train: &mut TrainRecord,
+    path: &str,
+    asked: &[usize],
+    password: impl FnOnce() -> anyhow::Result<SecretString>,
+) -> anyhow::Result<()>
+where
+    T: DiagramCell + Zeroize,
+{
+    require_input("recalled diagram", "an interactive terminal")?;
+    let password = password()?;
+    let title = match asked {
+        [r] => format!(
+            "Recall row {} of the {} diagram, other rows are not checked",
+            r + 1,
+            T::KIND
+        ),
+        _ => format!("Recall the full {} diagram", T::KIND),
+    };
+    let mut mx = diagram::edit::<T>(&title)?;
+    let marks = train.check(&mx, &password, asked);
+    mx.zeroize();
+    let marks = marks?;
+
+    for &r in asked {
+        match marks[r] {
+            Mark::Recalled => println!("Row {}: correct", r + 1),
+            _ => println!("Row {}: wrong", r + 1),
+        }
+    }
+    let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    train.append(path, Quiz { time, marks })
+}
+
+/// Recall of each row in recent quizzes and the row to practice next.
+fn print_stats(train: &TrainRecord) {
+    let last = match train.quizzes.last() {
+        Some(quiz) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            match now.saturating_sub(quiz.time) / 86400 {
+                0 => ", last today".to_owned(),
+                1 => ", last yesterday".to_owned(),
+                days => format!(", last {days} days ago"),
+            }
+        }
+        None => String::new(),
+    };
+    let quizzes = match train.quizzes.len() {
+        1 => "1 quiz".to_owned(),
+        n => format!("{n} quizzes"),
+    };
+    println!("Training of a {} diagram: {quizzes}{last}", train.kind);
+    for r in 0..7 {
+        match train.recall(r, RECENT_QUIZZES) {
+            (_, 0) => println!("Row {}: not asked yet", r + 1),
+            (recalled, asked) => println!("Row {}: correct {recalled} of the last {asked}", r + 1),
+        }
+    }
+
+    let full: Vec<&Quiz> = train
+        .quizzes
+        .iter()
+        .filter(|q| !q.marks.contains(&Mark::Skipped))
+        .collect();
+    if !full.is_empty() {
+        let recalled = full
+            .iter()
+            .filter(|q| q.marks.iter().all(|&m| m == Mark::Recalled))
+            .count();
+        println!("Full diagram: correct {recalled} of {}", full.len());
+    }
+
+    let next: Vec<String> = train
+        .weakest_rows()
+        .iter()
+        .map(|r| (r + 1).to_string())
+        .collect();
+    match next.len() {
+        1 => println!("Practice next: row {}", next[0]),
+        _ => println!("Practice next: rows {}", next.join(", ")),
+    }
+}
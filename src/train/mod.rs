@@ -0,0 +1,5 @@
+mod arg;
+mod execute;
+mod record;
+
+pub use arg::TrainCommand;
@@ -0,0 +1,345 @@
+//! Training record of a diagram.
+//!
+//! The record keeps a salted scrypt commitment of each row, never a cell,
+//! and the results of past quizzes:
+//! ```text
+//! format = 1
+//! type = simple
+//! scrypt = 17,8,1
+//! salt = 8f1c..
+//! row = 3a5e..
+//! quiz = 1760000000 ..+-...
+//! ```
+//! A quiz marks each row `+` recalled, `-` missed or `.` not asked.
+//!
+//! Threat model: a row holds a few cells, which a guesser could find one row at a time
+//! if its commitment took the cells alone. So each commitment also takes the diagram
+//! password, which the file does not hold: the file alone tests no guess of the diagram.
+//! Anyone with the file and the password can still test guesses row by row, as anyone
+//! with the password can test guesses of the whole diagram against its wallets; keep both
+//! private. The quiz results are kept in plaintext.
+
+use crate::diagram::DiagramCell;
+use anyhow::{anyhow, bail};
+use artimonist::Matrix;
+use artimonist::bitcoin::hex::{DisplayHex, FromHex};
+use std::io::Write;
+use zeroize::Zeroizing;
+
+/// Latest version of the training record format
+const RECORD_VERSION: u32 = 1;
+/// Quizzes a row is rated by
+pub const RECENT_QUIZZES: usize = 3;
+
+/// Result of a row in a quiz
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mark {
+    Skipped,
+    Recalled,
+    Missed,
+}
+
+impl Mark {
+    fn symbol(self) -> char {
+        match self {
+            Mark::Skipped => '.',
+            Mark::Recalled => '+',
+            Mark::Missed => '-',
+        }
+    }
+}
+
+/// Quiz time in seconds since the unix epoch, with the marks of the rows
+#[derive(Debug, PartialEq)]
+pub struct Quiz {
+    pub time: u64,
+    pub marks: [Mark; 7],
+}
+
+impl Quiz {
+    fn to_line(&self) -> String {
+        let marks: String = self.marks.iter().map(|m| m.symbol()).collect();
+        format!("quiz = {} {marks}\n", self.time)
+    }
+}
+
+#[derive(Debug)]
+pub struct TrainRecord {
+    /// Diagram type
+    pub kind: String,
+    /// scrypt parameters: log2 of cost, block size and parallelism
+    params: (u8, u32, u32),
+    salt: [u8; 16],
+    rows: [[u8; 32]; 7],
+    pub quizzes: Vec<Quiz>,
+}
+
+impl TrainRecord {
+    /// Commit to the rows of a diagram and its password under a new random salt.
+    pub fn commit<T: DiagramCell>(
+        mx: &Matrix<T, 7, 7>,
+        password: &str,
+        log_n: u8,
+    ) -> anyhow::Result<Self> {
+        let mut salt = [0; 16];
+        getrandom::getrandom(&mut salt)?;
+        let mut record = Self {
+            kind: T::KIND.to_owned(),
+            params: (
+                log_n,
+                scrypt::Params::RECOMMENDED_R,
+                scrypt::Params::RECOMMENDED_P,
+            ),
+            salt,
+            rows: [[0; 32]; 7],
+            quizzes: vec![],
+        };
+        for r in 0..7 {
+            record.rows[r] = record.row_hash(mx, password, r)?;
+        }
+        Ok(record)
+    }
+
+    /// Mark the asked rows of a recalled diagram, a wrong password misses them all.
+    pub fn check<T: DiagramCell>(
+        &self,
+        mx: &Matrix<T, 7, 7>,
+        password: &str,
+        asked: &[usize],
+    ) -> anyhow::Result<[Mark; 7]> {
+        if T::KIND != self.kind {
+            bail!("the record is of a {} diagram", self.kind);
+        }
+        let mut marks = [Mark::Skipped; 7];
+        for &r in asked {
+            marks[r] = match self.row_hash(mx, password, r)? == self.rows[r] {
+                true => Mark::Recalled,
+                false => Mark::Missed,
+            };
+        }
+        Ok(marks)
+    }
+
+    /// Salted scrypt hash of the password and the cells of a row.
+    fn row_hash<T: DiagramCell>(
+        &self,
+        mx: &Matrix<T, 7, 7>,
+        password: &str,
+        r: usize,
+    ) -> anyhow::Result<[u8; 32]> {
+        // the password led by its length, so it never runs into the cells
+        let mut canonical = Zeroizing::new(format!("{}:{password}\n", password.len()));
+        canonical.push_str(&format!("{}\n", T::KIND));
+        for (c, cell) in mx[r].iter().enumerate() {
+            if let Some(v) = cell {
+                let text = Zeroizing::new(v.to_text());
+                canonical.push_str(&format!("{}={}\n", c + 1, text.as_str()));
+            }
+        }
+        // each row has its own salt, equal rows of a diagram hash differently
+        let mut salt = self.salt.to_vec();
+        salt.push(r as u8);
+
+        let (log_n, block, parallel) = self.params;
+        let params = scrypt::Params::new(log_n, block, parallel, 32).map_err(|e| anyhow!("{e}"))?;
+        let mut hash = [0; 32];
+        scrypt::scrypt(canonical.as_bytes(), &salt, &params, &mut hash)
+            .map_err(|e| anyhow!("{e}"))?;
+        Ok(hash)
+    }
+
+    /// Recalled and asked counts of a row over the recent quizzes which asked it.
+    pub fn recall(&self, r: usize, recent: usize) -> (usize, usize) {
+        let marks: Vec<Mark> = self
+            .quizzes
+            .iter()
+            .rev()
+            .map(|q| q.marks[r])
+            .filter(|&m| m != Mark::Skipped)
+            .take(recent)
+            .collect();
+        let recalled = marks.iter().filter(|&&m| m == Mark::Recalled).count();
+        (recalled, marks.len())
+    }
+
+    /// Rows recalled least in recent quizzes, rows never asked count as half recalled.
+    pub fn weakest_rows(&self) -> Vec<usize> {
+        let score = |r: usize| {
+            let (recalled, asked) = self.recall(r, RECENT_QUIZZES);
+            (recalled as f64 + 1.0) / (asked as f64 + 2.0)
+        };
+        let min = (0..7).map(score).fold(f64::INFINITY, f64::min);
+        (0..7).filter(|&r| score(r) == min).collect()
+    }
+
+    pub fn to_text(&self) -> String {
+        let (log_n, block, parallel) = self.params;
+        let mut text = String::from(
+            "# Artimonist training record\n\
+             # Anyone with this file and the password can test guesses of the diagram, keep it private.\n",
+        );
+        text += &format!("format = {RECORD_VERSION}\n");
+        text += &format!("type = {}\n", self.kind);
+        text += &format!("scrypt = {log_n},{block},{parallel}\n");
+        text += &format!("salt = {}\n", self.salt.to_lower_hex_string());
+        for row in &self.rows {
+            text += &format!("row = {}\n", row.to_lower_hex_string());
+        }
+        for quiz in &self.quizzes {
+            text += &quiz.to_line();
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut record = Self {
+            kind: String::new(),
+            params: (0, 0, 0),
+            salt: [0; 16],
+            rows: [[0; 32]; 7],
+            quizzes: vec![],
+        };
+        let mut rows = 0;
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |msg: &str| anyhow!("line {}: {msg}", i + 1);
+            let (key, value) = line
+                .split_once('=')
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .ok_or_else(|| error("expected `key = value`"))?;
+            match key {
+                "format" if value == RECORD_VERSION.to_string() => {}
+                "format" => return Err(error(&format!("unsupported format `{value}`"))),
+                "type" if matches!(value, "simple" | "complex") => record.kind = value.to_owned(),
+                "scrypt" => {
+                    let mut parts = value.split(',').map(str::trim);
+                    let mut next = || parts.next().and_then(|v| v.parse::<u32>().ok());
+                    match (next(), next(), next()) {
+                        (Some(log_n @ 1..64), Some(block), Some(parallel)) => {
+                            record.params = (log_n as u8, block, parallel)
+                        }
+                        _ => return Err(error("invalid scrypt parameters")),
+                    }
+                }
+                "salt" => {
+                    record.salt = <[u8; 16]>::from_hex(value).map_err(|_| error("invalid salt"))?
+                }
+                "row" if rows < 7 => {
+                    record.rows[rows] =
+                        <[u8; 32]>::from_hex(value).map_err(|_| error("invalid row hash"))?;
+                    rows += 1;
+                }
+                "quiz" => record
+                    .quizzes
+                    .push(parse_quiz(value).ok_or_else(|| error("invalid quiz"))?),
+                _ => return Err(error(&format!("unexpected `{key}`"))),
+            }
+        }
+        if record.kind.is_empty() || record.params.0 == 0 || rows < 7 {
+            bail!("incomplete training record, expected type, scrypt, salt and 7 rows");
+        }
+        Ok(record)
+    }
+
+    /// Create a new record file, readable by the owner only.
+    pub fn to_file(&self, path: &str) -> anyhow::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut f = options
+            .open(path)
+            .map_err(|e| anyhow!("cannot create training record `{path}`: {e}"))?;
+        f.write_all(self.to_text().as_bytes())?;
+        Ok(())
+    }
+
+    pub fn from_file(path: &str) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("cannot read training record `{path}`: {e}"))?;
+        Self::parse(&text).map_err(|e| anyhow!("invalid training record `{path}`: {e}"))
+    }
+
+    /// Record a quiz, appended to the record file.
+    pub fn append(&mut self, path: &str, quiz: Quiz) -> anyhow::Result<()> {
+        let mut f = std::fs::OpenOptions::new().append(true).open(path)?;
+        f.write_all(quiz.to_line().as_bytes())?;
+        self.quizzes.push(quiz);
+        Ok(())
+    }
+}
+
+fn parse_quiz(value: &str) -> Option<Quiz> {
+    let (time, marks) = value.split_once(' ')?;
+    let marks: Vec<Mark> = marks
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '.' => Some(Mark::Skipped),
+            '+' => Some(Mark::Recalled),
+            '-' => Some(Mark::Missed),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    Some(Quiz {
+        time: time.parse().ok()?,
+        marks: marks.try_into().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod record_test {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut mx: Matrix<char, 7, 7> = [[None; 7]; 7];
+        mx[0][3] = Some('₿');
+        mx[2][2] = Some('$');
+        mx[2][5] = Some('≈');
+
+        // low cost for the test, the command uses the recommended one
+        let mut record = TrainRecord::commit(&mx, "123456", 4).unwrap();
+        record.quizzes.push(Quiz {
+            time: 1760000000,
+            marks: record.check(&mx, "123456", &[0, 1, 2]).unwrap(),
+        });
+        let text = record.to_text();
+        assert!(!text.contains('₿') && !text.contains('$'));
+
+        let record = TrainRecord::parse(&text).unwrap();
+        assert_eq!(record.to_text(), text);
+        assert_eq!(record.quizzes[0].marks[..3], [Mark::Recalled; 3]);
+
+        // a missed cell fails its row only
+        let mut recalled = mx;
+        recalled[2][5] = Some('=');
+        let marks = record.check(&recalled, "123456", &[0, 2]).unwrap();
+        assert_eq!(marks[..3], [Mark::Recalled, Mark::Skipped, Mark::Missed]);
+
+        // the rows are bound to the password, even an empty row
+        let marks = record.check(&mx, "654321", &[0, 1, 2]).unwrap();
+        assert_eq!(marks[..3], [Mark::Missed; 3]);
+
+        let strings: Matrix<String, 7, 7> = Default::default();
+        assert!(record.check(&strings, "123456", &[0]).is_err());
+    }
+
+    #[test]
+    fn test_weakest_rows() {
+        let mut record = TrainRecord::commit(&[[None::<char>; 7]; 7], "123456", 4).unwrap();
+        assert_eq!(record.weakest_rows(), (0..7).collect::<Vec<_>>());
+
+        for marks in ["+++-+++", "..-....", "+++++++"] {
+            record
+                .quizzes
+                .push(parse_quiz(&format!("0 {marks}")).unwrap());
+        }
+        assert_eq!(record.recall(2, 3), (2, 3));
+        assert_eq!(record.weakest_rows(), [3]);
+    }
+}
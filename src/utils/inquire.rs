@@ -1,6 +1,34 @@
+use super::secure::SecretString;
 use super::unicode::unicode_decode;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-pub fn inquire_password(as_salt: bool) -> anyhow::Result<String> {
+/// Prompts are disabled by `--no-input` or when stdin is not a terminal.
+static INTERACTIVE: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable interactive prompts.
+pub fn set_interactive(enabled: bool) {
+    INTERACTIVE.store(enabled, Ordering::SeqCst);
+}
+
+/// Input is missing and prompts are disabled.
+#[derive(thiserror::Error, Debug)]
+#[error("missing {input}, supply it with {hint} (interactive input is disabled)")]
+pub struct MissingInput {
+    /// Name of the missing input
+    pub input: &'static str,
+    /// Flags which supply the input
+    pub hint: &'static str,
+}
+
+/// Check prompts are enabled before asking user for an input.
+pub fn require_input(input: &'static str, hint: &'static str) -> Result<(), MissingInput> {
+    match INTERACTIVE.load(Ordering::SeqCst) {
+        true => Ok(()),
+        false => Err(MissingInput { input, hint }),
+    }
+}
+
+pub fn inquire_password(as_salt: bool) -> anyhow::Result<SecretString> {
     use inquire::validator::Validation;
 
     const INVALID_MSG: &str = "Encryption key must have at least 5 characters.";
@@ -24,8 +52,51 @@ pub fn inquire_password(as_salt: bool) -> anyhow::Result<String> {
         } else {
             "Input encryption key. (Toggle display by CTRL+R)"
         })
-        .prompt()?;
-    Ok(unicode_decode(&pwd))
+        .prompt()
+        .map(SecretString::new)?;
+    Ok(unicode_decode(&pwd).into())
+}
+
+/// Prompt user for the BIP39 passphrase of mnemonic, empty is allowed.
+pub fn inquire_passphrase() -> anyhow::Result<SecretString> {
+    let pwd = inquire::Password::new("Passphrase: ")
+        .with_display_mode(inquire::PasswordDisplayMode::Masked)
+        .with_display_toggle_enabled()
+        .with_custom_confirmation_message("Passphrase (confirm):")
+        .with_custom_confirmation_error_message("The passphrases don't match.")
+        .with_formatter(&|_| "Input received".into())
+        .with_help_message(
+            "BIP39 passphrase of mnemonic, empty for none. (Toggle display by CTRL+R)",
+        )
+        .prompt()
+        .map(SecretString::new)?;
+    Ok(unicode_decode(&pwd).into())
+}
+
+/// Prompt user for mnemonic or master key with masked input.
+pub fn inquire_key() -> anyhow::Result<SecretString> {
+    let key = inquire::Password::new("Mnemonic or master key: ")
+        .with_display_mode(inquire::PasswordDisplayMode::Masked)
+        .with_display_toggle_enabled()
+        .without_confirmation()
+        .with_formatter(&|_| "Input received".into())
+        .with_help_message("Input is masked. (Toggle display by CTRL+R)")
+        .prompt()
+        .map(SecretString::new)?;
+    Ok(key)
+}
+
+/// Prompt user for a generated child with masked input.
+pub fn inquire_child() -> anyhow::Result<SecretString> {
+    let child = inquire::Password::new("Generated child: ")
+        .with_display_mode(inquire::PasswordDisplayMode::Masked)
+        .with_display_toggle_enabled()
+        .without_confirmation()
+        .with_formatter(&|_| "Input received".into())
+        .with_help_message("Input is masked. (Toggle display by CTRL+R)")
+        .prompt()
+        .map(SecretString::new)?;
+    Ok(child)
 }
 
 use artimonist::Language;
@@ -39,3 +110,12 @@ pub fn select_language(langs: &[Language]) -> anyhow::Result<Language> {
         .prompt()?;
     Ok(choice.parse()?)
 }
+
+/// Prompt user to confirm an action, default to no.
+pub fn inquire_confirm(message: &str, help: &str) -> anyhow::Result<bool> {
+    let confirmed = inquire::Confirm::new(message)
+        .with_default(false)
+        .with_help_message(help)
+        .prompt()?;
+    Ok(confirmed)
+}
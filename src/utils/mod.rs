@@ -1,5 +1,20 @@
 mod inquire;
+mod search;
+mod secret;
+mod secure;
 mod unicode;
 
-pub use inquire::{inquire_password, select_language};
-pub use unicode::{unicode_decode, unicode_encode};
+pub use inquire::{MissingInput, require_input, set_interactive};
+pub use inquire::{
+    inquire_child, inquire_confirm, inquire_key, inquire_passphrase, inquire_password,
+    select_language,
+};
+pub use search::{duration_text, parallel_search, parallel_search_from};
+pub use secret::{
+    ChildInput, KeyInput, PassphraseInput, PasswordInput, SecretInput, WalletSecrets,
+};
+pub use secure::{SecretString, SecretXpriv, harden_process};
+pub use unicode::{
+    display_width, nfc_secret, nfkd_secret, starts_with_form, unicode_decode, unicode_decode_forms,
+    unicode_describe, unicode_encode, unicode_warnings,
+};
@@ -0,0 +1,219 @@
+//! Search over numbered candidates on all cores, with progress on stderr.
+
+use std::collections::BTreeSet;
+use std::io::IsTerminal;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Check candidates `0..total` on `threads` threads until one is found.
+/// Candidates are taken in order, but another thread may find a later one first.
+pub fn parallel_search<R, F>(total: u64, threads: usize, check: F) -> anyhow::Result<Option<R>>
+where
+    R: Send,
+    F: Fn(u64) -> anyhow::Result<Option<R>> + Sync,
+{
+    parallel_search_from(0, total, threads, check, |_| Ok(()))
+}
+
+/// Check candidates `start..total` as `parallel_search` does, calling `checkpoint`
+/// now and then and at the end with the count below which all are checked.
+pub fn parallel_search_from<R, F, C>(
+    start: u64,
+    total: u64,
+    threads: usize,
+    check: F,
+    mut checkpoint: C,
+) -> anyhow::Result<Option<R>>
+where
+    R: Send,
+    F: Fn(u64) -> anyhow::Result<Option<R>> + Sync,
+    C: FnMut(u64) -> anyhow::Result<()>,
+{
+    // next candidate and the ones being checked, the least of them is the checkpoint
+    let pending = Mutex::new((start, BTreeSet::<u64>::new()));
+    let checked = AtomicU64::new(start);
+    let stop = AtomicBool::new(false);
+    let found: Mutex<Option<anyhow::Result<R>>> = Mutex::new(None);
+    let lock = || pending.lock().unwrap_or_else(|e| e.into_inner());
+    let done_below = || {
+        let pending = lock();
+        pending.1.first().copied().unwrap_or(pending.0).min(total)
+    };
+
+    let saved = std::thread::scope(|s| {
+        for _ in 0..threads.max(1) {
+            s.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    let i = {
+                        let mut pending = lock();
+                        let i = pending.0;
+                        if i >= total {
+                            break;
+                        }
+                        pending.0 += 1;
+                        pending.1.insert(i);
+                        i
+                    };
+                    let result = check(i).transpose();
+                    lock().1.remove(&i);
+                    checked.fetch_add(1, Ordering::Relaxed);
+                    if let Some(result) = result {
+                        // an error stops the search as a found candidate does
+                        let mut found = found.lock().unwrap_or_else(|e| e.into_inner());
+                        found.get_or_insert(result);
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+
+        let mut progress = Progress::new(start, total);
+        let mut saved_at = Instant::now();
+        loop {
+            std::thread::sleep(Duration::from_millis(200));
+            let n = checked.load(Ordering::Relaxed);
+            if n >= total || stop.load(Ordering::Relaxed) {
+                break;
+            }
+            progress.report(n);
+            if saved_at.elapsed() >= CHECKPOINT_INTERVAL {
+                saved_at = Instant::now();
+                if let Err(e) = checkpoint(done_below()) {
+                    stop.store(true, Ordering::Relaxed);
+                    progress.clear();
+                    return Err(e);
+                }
+            }
+        }
+        progress.clear();
+        Ok(())
+    });
+    // all threads are done here, every candidate taken is checked
+    saved.and_then(|_| checkpoint(done_below()))?;
+
+    found
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .transpose()
+}
+
+/// Time between checkpoints of a search
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Checked count, speed and remaining time, redrawn on a terminal
+/// or printed as a line now and then otherwise.
+struct Progress {
+    /// Candidates checked before this run
+    skipped: u64,
+    total: u64,
+    start: Instant,
+    last: Option<Instant>,
+    terminal: bool,
+}
+
+impl Progress {
+    fn new(skipped: u64, total: u64) -> Self {
+        Self {
+            skipped,
+            total,
+            start: Instant::now(),
+            last: None,
+            terminal: std::io::stderr().is_terminal(),
+        }
+    }
+
+    fn report(&mut self, checked: u64) {
+        let interval = match self.terminal {
+            true => Duration::from_secs(1),
+            false => Duration::from_secs(30),
+        };
+        let since = self.last.unwrap_or(self.start);
+        if since.elapsed() < interval {
+            return;
+        }
+        self.last = Some(Instant::now());
+
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let rate = (checked - self.skipped) as f64 / elapsed;
+        let left = match checked - self.skipped {
+            0 => "unknown time".to_owned(),
+            _ => duration_text((self.total - checked) as f64 / rate),
+        };
+        let line = format!(
+            "Checked {checked} of {}, {rate:.1}/s, {left} left",
+            self.total
+        );
+        match self.terminal {
+            true => eprint!("\r{line:<60}"),
+            false => eprintln!("{line}"),
+        }
+    }
+
+    fn clear(&self) {
+        if self.terminal && self.last.is_some() {
+            eprint!("\r{:<60}\r", "");
+        }
+    }
+}
+
+/// Rough duration like `40s`, `12m`, `3h 20m` or `5 days`.
+pub fn duration_text(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        3600..172800 => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{} days", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod search_test {
+    use super::*;
+
+    #[test]
+    fn test_parallel_search() {
+        let found = parallel_search(1000, 4, |i| Ok((i * i == 529).then_some(i))).unwrap();
+        assert_eq!(found, Some(23));
+        let found = parallel_search(1000, 4, |_| Ok(None::<u64>)).unwrap();
+        assert_eq!(found, None);
+        let result = parallel_search(1000, 4, |i| match i {
+            7 => Err(anyhow::anyhow!("failed")),
+            _ => Ok(None::<u64>),
+        });
+        assert!(result.is_err());
+
+        // resumed from a checkpoint, candidates before it are not checked again
+        let mut saved = vec![];
+        let found = parallel_search_from(
+            30,
+            1000,
+            4,
+            |i| Ok((i % 23 == 0).then_some(i)),
+            |n| {
+                saved.push(n);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert!(found.is_some_and(|i| i >= 46 && i % 23 == 0));
+        assert!(saved.last().is_some_and(|&n| n > found.unwrap()));
+        let mut saved = 0;
+        let found = parallel_search_from(
+            30,
+            1000,
+            4,
+            |_| Ok(None::<u64>),
+            |n| {
+                saved = n;
+                Ok(())
+            },
+        );
+        assert!(found.unwrap().is_none());
+        assert_eq!(saved, 1000);
+
+        assert_eq!(duration_text(3725.0), "1h 2m");
+        assert_eq!(duration_text(86400.0 * 3.0), "3 days");
+    }
+}
@@ -0,0 +1,243 @@
+use super::inquire::{inquire_key, inquire_passphrase, inquire_password, require_input};
+use super::secure::SecretString;
+use super::unicode::{nfc_secret, nfkd_secret};
+use anyhow::anyhow;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Declare input channels of a secret: stdin, file descriptor and file.
+macro_rules! secret_input {
+    ($name:ident, $secret:literal, $value:literal, $stdin:literal, $fd:literal, $file:literal) => {
+        #[doc = concat!("Input channels of ", $secret)]
+        #[derive(clap::Args, Debug, Default)]
+        pub struct $name {
+            #[clap(long = $stdin, id = $stdin, conflicts_with_all = [$fd, $file],
+                                      help = concat!("Read ", $secret, " from stdin"))]
+            pub stdin: bool,
+
+            #[clap(long = $fd, id = $fd, value_name = "FD", conflicts_with = $file,
+                                      value_parser = clap::value_parser!(i32).range(3..),
+                                      help = concat!("Read ", $secret, " from file descriptor"))]
+            pub fd: Option<i32>,
+
+            #[clap(long = $file, id = $file, value_name = "PATH",
+                                      help = concat!("Read ", $secret, " from file"))]
+            pub file: Option<String>,
+        }
+
+        impl SecretInput for $name {
+            const SECRET: &str = $secret;
+            const HINT: &str = concat!($value, ", --", $stdin, ", --", $fd, " or --", $file);
+
+            fn read_secret(&self) -> anyhow::Result<Option<SecretString>> {
+                match (self.stdin, self.fd, &self.file) {
+                    (true, _, _) => read_stdin().map(Some),
+                    (_, Some(fd), _) => read_fd(fd).map(Some),
+                    (_, _, Some(file)) => read_file(file).map(Some),
+                    _ => Ok(None),
+                }
+            }
+        }
+    };
+}
+
+secret_input!(
+    KeyInput,
+    "mnemonic or master key",
+    "<MNEMONIC|MASTER_KEY>, ARTIMONIST_KEY",
+    "key-stdin",
+    "key-fd",
+    "key-file"
+);
+secret_input!(
+    PasswordInput,
+    "password",
+    "ARTIMONIST_PASSWORD",
+    "password-stdin",
+    "password-fd",
+    "password-file"
+);
+secret_input!(
+    PassphraseInput,
+    "passphrase",
+    "--passphrase, --no-passphrase, ARTIMONIST_PASSPHRASE",
+    "passphrase-stdin",
+    "passphrase-fd",
+    "passphrase-file"
+);
+secret_input!(
+    ChildInput,
+    "generated child",
+    "<CHILD>",
+    "child-stdin",
+    "child-fd",
+    "child-file"
+);
+secret_input!(
+    EncryptInput,
+    "encryption password",
+    "--encrypt-with, ARTIMONIST_ENCRYPT_WITH",
+    "encrypt-with-stdin",
+    "encrypt-with-fd",
+    "encrypt-with-file"
+);
+
+impl KeyInput {
+    /// Resolve the master key from argument, input channels or masked prompt.
+    pub fn master_key<'a, K>(&self, key: &'a mut Option<K>) -> anyhow::Result<&'a K>
+    where
+        K: std::str::FromStr<Err = anyhow::Error>,
+    {
+        if let Some(secret) = self.read_secret()? {
+            *key = Some(secret.parse()?);
+        } else if key.is_none() {
+            *key = Some(self.prompt(inquire_key)?.parse()?);
+        }
+        Ok(key.as_ref().unwrap())
+    }
+}
+
+/// Secret read from non-argv channels
+pub trait SecretInput {
+    /// Name of the secret
+    const SECRET: &str;
+    /// Flags which supply the secret
+    const HINT: &str;
+
+    /// Read secret from the selected channel, `None` if no channel selected.
+    fn read_secret(&self) -> anyhow::Result<Option<SecretString>>;
+
+    /// Resolve secret in order: channel, argument or environment, prompt.
+    fn resolve(
+        &self,
+        value: Option<&SecretString>,
+        prompt: impl FnOnce() -> anyhow::Result<SecretString>,
+    ) -> anyhow::Result<SecretString> {
+        match (self.read_secret()?, value) {
+            (Some(secret), _) => Ok(secret),
+            (None, Some(v)) => Ok(v.clone()),
+            (None, None) => self.prompt(prompt),
+        }
+    }
+
+    /// Prompt user for the secret if interactive input is enabled.
+    fn prompt(
+        &self,
+        prompt: impl FnOnce() -> anyhow::Result<SecretString>,
+    ) -> anyhow::Result<SecretString> {
+        require_input(Self::SECRET, Self::HINT)?;
+        prompt()
+    }
+}
+
+/// Stdin can supply only one secret per run.
+static STDIN_USED: AtomicBool = AtomicBool::new(false);
+
+fn read_stdin() -> anyhow::Result<SecretString> {
+    if STDIN_USED.swap(true, Ordering::SeqCst) {
+        return Err(anyhow!("stdin can only supply one secret"));
+    }
+    read_secret_from(std::io::stdin())
+}
+
+#[cfg(unix)]
+fn read_fd(fd: i32) -> anyhow::Result<SecretString> {
+    use std::os::fd::FromRawFd;
+
+    // the value parser keeps out stdin, stdout, stderr and negative numbers
+    if unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+        return Err(anyhow!("file descriptor {fd} is not open"));
+    }
+    // SAFETY: the descriptor is open, handed over by the caller and owned by us from now on.
+    read_secret_from(unsafe { std::fs::File::from_raw_fd(fd) })
+}
+
+#[cfg(not(unix))]
+fn read_fd(_fd: i32) -> anyhow::Result<SecretString> {
+    Err(anyhow!(
+        "reading from file descriptor is only available on unix"
+    ))
+}
+
+fn read_file(path: impl AsRef<Path>) -> anyhow::Result<SecretString> {
+    read_secret_from(std::fs::File::open(path)?)
+}
+
+/// Read a secret into a preallocated buffer, so that no reallocation leaves copies behind.
+fn read_secret_from(reader: impl Read) -> anyhow::Result<SecretString> {
+    const CAPACITY: usize = 4096;
+
+    let mut s = SecretString::new(String::with_capacity(CAPACITY));
+    let buf = s.as_mut_string();
+    reader.take(CAPACITY as u64).read_to_string(buf)?;
+    if buf.len() == CAPACITY {
+        return Err(anyhow!("secret is longer than {CAPACITY} bytes"));
+    }
+    trim_line_end(buf);
+    Ok(s)
+}
+
+/// Remove one trailing line ending, keep other whitespaces of the secret.
+fn trim_line_end(s: &mut String) {
+    if s.ends_with('\n') {
+        s.pop();
+        if s.ends_with('\r') {
+            s.pop();
+        }
+    }
+}
+
+/// Passphrase of mnemonic and password of private keys
+#[derive(clap::Args, Debug)]
+pub struct WalletSecrets {
+    /// BIP39 passphrase of mnemonic
+    #[clap(long, env = "ARTIMONIST_PASSPHRASE", hide_env_values = true)]
+    pub passphrase: Option<SecretString>,
+
+    /// Passphrase input channels
+    #[clap(flatten)]
+    pub passphrase_input: PassphraseInput,
+
+    /// Use mnemonic without BIP39 passphrase
+    #[clap(long, conflicts_with_all = ["passphrase-stdin", "passphrase-fd", "passphrase-file"])]
+    pub no_passphrase: bool,
+
+    /// Password for BIP38 encryption of private keys
+    #[clap(
+        long,
+        value_name = "PASSWORD",
+        env = "ARTIMONIST_ENCRYPT_WITH",
+        hide_env_values = true
+    )]
+    pub encrypt_with: Option<SecretString>,
+
+    /// Encryption password input channels
+    #[clap(flatten)]
+    pub encrypt_input: EncryptInput,
+
+    /// Password as both passphrase and encryption key
+    #[clap(hide = true, long)]
+    pub password: Option<SecretString>,
+}
+
+impl WalletSecrets {
+    /// BIP39 passphrase in NFKD, prompt for it if not provided.
+    pub fn passphrase(&self) -> anyhow::Result<SecretString> {
+        if self.no_passphrase {
+            return Ok(SecretString::default());
+        }
+        let value = self.passphrase.as_ref().or(self.password.as_ref());
+        let passphrase = self.passphrase_input.resolve(value, inquire_passphrase)?;
+        Ok(nfkd_secret(&passphrase))
+    }
+
+    /// BIP38 encryption password in NFC, prompt for it if not provided.
+    pub fn encryption(&self) -> anyhow::Result<SecretString> {
+        let value = self.encrypt_with.as_ref().or(self.password.as_ref());
+        let password = self
+            .encrypt_input
+            .resolve(value, || inquire_password(false))?;
+        Ok(nfc_secret(&password))
+    }
+}
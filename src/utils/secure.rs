@@ -0,0 +1,235 @@
+use artimonist::Xpriv;
+#[cfg(unix)]
+use std::collections::BTreeMap;
+use zeroize::Zeroize;
+
+/// Disable core dumps and, on linux, ptrace attach by other processes of the same user.
+#[cfg(unix)]
+pub fn harden_process() -> std::io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: `limit` is a valid rlimit for the duration of the call.
+    if unsafe { libc::setrlimit(libc::RLIMIT_CORE, &limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    #[cfg(target_os = "linux")]
+    // SAFETY: PR_SET_DUMPABLE takes a plain integer argument.
+    if unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn harden_process() -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Pages locked by live secrets and how many of them each holds. Locks of the kernel don't nest,
+/// the first `munlock` of a page unlocks it for every secret on it.
+#[cfg(unix)]
+static LOCKED_PAGES: std::sync::Mutex<BTreeMap<usize, usize>> =
+    std::sync::Mutex::new(BTreeMap::new());
+
+/// Numbers of the pages a buffer spans.
+#[cfg(unix)]
+fn pages(buf: &[u8]) -> std::ops::Range<usize> {
+    let start = buf.as_ptr() as usize;
+    start / page_size()..(start + buf.len()).div_ceil(page_size())
+}
+
+#[cfg(unix)]
+fn page_size() -> usize {
+    // SAFETY: sysconf has no preconditions.
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Lock the pages of a buffer in memory, keep it out of swap where available.
+/// Locking is best effort: it is skipped silently beyond `RLIMIT_MEMLOCK`, and only zeroing
+/// on drop is guaranteed. A page stays locked until no live secret is on it.
+fn lock_pages(buf: &[u8]) {
+    #[cfg(unix)]
+    if !buf.is_empty() {
+        let mut locked = LOCKED_PAGES.lock().unwrap_or_else(|e| e.into_inner());
+        // SAFETY: the range is a live allocation owned by the caller.
+        unsafe { libc::mlock(buf.as_ptr().cast(), buf.len()) };
+        for page in pages(buf) {
+            *locked.entry(page).or_default() += 1;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = buf;
+}
+
+/// Unlock the pages of a buffer that no other secret holds.
+fn unlock_pages(buf: &[u8]) {
+    #[cfg(unix)]
+    if !buf.is_empty() {
+        let mut locked = LOCKED_PAGES.lock().unwrap_or_else(|e| e.into_inner());
+        for page in pages(buf) {
+            let Some(count) = locked.get_mut(&page) else {
+                continue;
+            };
+            *count -= 1;
+            if *count == 0 {
+                locked.remove(&page);
+                // SAFETY: munlock takes any mapped range, the page holds the caller's buffer.
+                unsafe { libc::munlock((page * page_size()) as *const libc::c_void, page_size()) };
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = buf;
+}
+
+/// String holding a secret: locked in memory, zeroed on drop, redacted in `Debug`.
+#[derive(Default)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(s: String) -> Self {
+        lock_pages(Self::allocation(&s));
+        Self(s)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Mutable access for filling the buffer, which must stay within its capacity.
+    pub(super) fn as_mut_string(&mut self) -> &mut String {
+        &mut self.0
+    }
+
+    /// Whole heap allocation of the string, including spare capacity.
+    fn allocation(s: &String) -> &[u8] {
+        // SAFETY: `capacity` bytes starting at the buffer pointer belong to the allocation.
+        unsafe { std::slice::from_raw_parts(s.as_ptr(), s.capacity()) }
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+        unlock_pages(Self::allocation(&self.0));
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(s: String) -> Self {
+        Self::new(s)
+    }
+}
+
+impl std::str::FromStr for SecretString {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(s.to_owned()))
+    }
+}
+
+impl Clone for SecretString {
+    fn clone(&self) -> Self {
+        Self::new(self.0.clone())
+    }
+}
+
+impl std::ops::Deref for SecretString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretString(***)")
+    }
+}
+
+/// Extended private key erased on drop, redacted in `Debug`.
+#[derive(Clone)]
+pub struct SecretXpriv(Xpriv);
+
+impl SecretXpriv {
+    pub fn new(xpriv: Xpriv) -> Self {
+        Self(xpriv)
+    }
+}
+
+impl Drop for SecretXpriv {
+    fn drop(&mut self) {
+        self.0.private_key.non_secure_erase();
+        let chain_code: &mut [u8] = self.0.chain_code.as_mut();
+        chain_code.zeroize();
+    }
+}
+
+impl std::ops::Deref for SecretXpriv {
+    type Target = Xpriv;
+
+    fn deref(&self) -> &Xpriv {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for SecretXpriv {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretXpriv(***)")
+    }
+}
+
+#[cfg(test)]
+mod secure_test {
+    use super::*;
+
+    #[test]
+    fn test_secret_debug() {
+        let secret = SecretString::new("123456".to_owned());
+        assert_eq!(format!("{secret:?}"), "SecretString(***)");
+        assert_eq!(&*secret, "123456");
+
+        let xprv = "xprv9s21ZrQH143K4UoTfggaDMmCkfpe9UoALJsg38fDuE5aEmiP9eub61MJmkMfKVjRdM38StnFGo3nb4tGgXZ91LeZZFsG11u7paJzCk9memZ";
+        let secret = SecretXpriv::new(xprv.parse().unwrap());
+        assert_eq!(format!("{secret:?}"), "SecretXpriv(***)");
+        assert_eq!(secret.to_string(), xprv);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shared_pages() {
+        let first = SecretString::new("first secret".to_owned());
+        let second = SecretString::new("second secret".to_owned());
+        let first_pages = pages(SecretString::allocation(&first.0));
+        drop(second);
+        // the pages of the first secret stay locked, whether the second shared them or not
+        let locked = LOCKED_PAGES.lock().unwrap();
+        assert!(first_pages.clone().all(|page| locked.contains_key(&page)));
+    }
+
+    #[test]
+    fn test_command_debug() {
+        use crate::{Cli, Commands};
+        use clap::Parser;
+
+        const MNEMONIC: &str = "lake album jump occur hedgehog fantasy drama sauce oyster velvet gadget control behave hamster begin";
+        let cli = Cli::try_parse_from([
+            "artimonist",
+            "derive",
+            MNEMONIC,
+            "--passphrase=Secret#1",
+            "--encrypt-with=Secret#2",
+        ])
+        .unwrap();
+        let Commands::Derive(cmd) = cli.command else {
+            panic!("derive command expected");
+        };
+        let debug = format!("{cmd:?}");
+        assert!(!debug.contains("lake"));
+        assert!(!debug.contains("Secret#"));
+    }
+}
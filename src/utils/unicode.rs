@@ -1,4 +1,8 @@
-use std::{iter::Peekable, str::Chars};
+use super::SecretString;
+use unicode_normalization::UnicodeNormalization;
+use unicode_security::{MixedScript, skeleton};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 pub fn unicode_encode(s: &str) -> String {
     s.chars()
@@ -11,54 +15,255 @@ pub fn unicode_encode(s: &str) -> String {
         .collect()
 }
 
+/// Decode the `\u{..}` escapes of legacy diagram files and prompted passwords:
+/// `\u{2248}` in hex, or `\u{08776}` in decimal led by `0`.
+/// Other forms are kept as typed, so these inputs give the wallets they always gave.
 pub fn unicode_decode(s: &str) -> String {
-    let mut decoded = String::new();
-    let mut chars = s.chars().peekable();
-    while let Some(ch) = chars.next() {
-        if ch == '\\'
-            && chars.peek() == Some(&'u')
-            && let Some(unicode_char) = decode_char(&mut chars)
-        {
-            decoded.push(unicode_char);
-        } else {
-            decoded.push(ch);
+    decode(s, |s| {
+        let (ch, len) = decode_escape(s.strip_prefix("\\u{")?)?;
+        Some((Decoded::Char(ch), len + 3))
+    })
+}
+
+/// Decode the character forms typed in the diagram editor and in files of format 1:
+/// `\u{2248}`, `\u{08776}` (decimal), `U+2248`, `&asymp;`, `&#x2248;`,
+/// `\N{ALMOST EQUAL TO}` and emoji shortcodes like `:key:`.
+/// Text which is not a known form is kept as typed.
+pub fn unicode_decode_forms(s: &str) -> String {
+    decode(s, decode_prefix)
+}
+
+fn decode(s: &str, prefix: impl Fn(&str) -> Option<(Decoded, usize)>) -> String {
+    // shortcodes expand at most fourfold, reserve it so secrets are not copied by reallocation
+    let mut decoded = String::with_capacity(s.len() * 4);
+    let mut rest = s;
+    while let Some(ch) = rest.chars().next() {
+        match prefix(rest) {
+            Some((Decoded::Char(c), len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            Some((Decoded::Text(text), len)) => {
+                decoded.push_str(text);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push(ch);
+                rest = &rest[ch.len_utf8()..];
+            }
         }
     }
     decoded
 }
 
-fn decode_char(chars: &mut Peekable<Chars>) -> Option<char> {
+/// Text starts with a form which `unicode_decode_forms` replaces.
+pub fn starts_with_form(s: &str) -> bool {
+    decode_prefix(s).is_some()
+}
+
+/// Characters of a text by code point and name, ascii runs quoted:
+/// `"BTC", U+20BF BITCOIN SIGN`.
+pub fn unicode_describe(s: &str) -> String {
+    let mut parts: Vec<String> = vec![];
+    let mut ascii = String::new();
+    for ch in s.chars() {
+        if (' '..='~').contains(&ch) {
+            ascii.push(ch);
+            continue;
+        }
+        if !ascii.is_empty() {
+            parts.push(format!("{ascii:?}"));
+            ascii.clear();
+        }
+        parts.push(match unicode_names2::name(ch) {
+            Some(name) => format!("U+{:04X} {name}", ch as u32),
+            None => format!("U+{:04X}", ch as u32),
+        });
+    }
+    if !ascii.is_empty() {
+        parts.push(format!("{ascii:?}"));
+    }
+    parts.join(", ")
+}
+
+enum Decoded {
+    Char(char),
+    Text(&'static str),
+}
+
+/// Decode a form at the start of a text, with the length it takes.
+fn decode_prefix(s: &str) -> Option<(Decoded, usize)> {
+    const NAME_MAX_LEN: usize = 100; // longest character name has 88 letters
+    const ENTITY_MAX_LEN: usize = 40; // `&CounterClockwiseContourIntegral;`
+    const SHORTCODE_MAX_LEN: usize = 50;
+
+    if let Some(body) = s.strip_prefix("\\u{") {
+        let (ch, len) = decode_escape(body)?;
+        return Some((Decoded::Char(ch), len + 3));
+    }
+    if let Some(body) = s.strip_prefix("\\N{") {
+        let end = body.find('}').filter(|&end| end <= NAME_MAX_LEN)?;
+        let ch = unicode_names2::character(&body[..end])?;
+        return Some((Decoded::Char(ch), end + 4));
+    }
+    if let Some(body) = s.strip_prefix("U+") {
+        // 4 to 6 digits up to the end or a delimiter, `U+2248A` is no `≈A`
+        let digits = body
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(body.len());
+        if !(4..=6).contains(&digits) {
+            return None;
+        }
+        let ch = char::from_u32(u32::from_str_radix(&body[..digits], 16).ok()?)?;
+        return Some((Decoded::Char(ch), digits + 2));
+    }
+    if s.starts_with('&') {
+        let end = s.find(';').filter(|&end| end < ENTITY_MAX_LEN)?;
+        let entity = &s[..=end];
+        let ch = if let Some(hex) = entity[1..end].strip_prefix("#x") {
+            char::from_u32(u32::from_str_radix(hex, 16).ok()?)
+        } else if let Some(decimal) = entity[1..end].strip_prefix('#') {
+            char::from_u32(decimal.parse().ok()?)
+        } else {
+            let found = entities::ENTITIES.iter().find(|e| e.entity == entity)?;
+            return Some((Decoded::Text(found.characters), end + 1));
+        };
+        return Some((Decoded::Char(ch?), end + 1));
+    }
+    if let Some(body) = s.strip_prefix(':') {
+        let end = body.find(':').filter(|&end| end <= SHORTCODE_MAX_LEN)?;
+        let code = &body[..end];
+        if code.is_empty()
+            || !code
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"_+-".contains(&b))
+        {
+            return None;
+        }
+        let emoji = emojis::get_by_shortcode(code)?;
+        return Some((Decoded::Text(emoji.as_str()), end + 2));
+    }
+    None
+}
+
+/// Decode the body of `\u{..}`: hex digits, or decimal digits led by `0`.
+fn decode_escape(body: &str) -> Option<(char, usize)> {
     const DECIMAL_MAX_LEN: usize = 8; // char::MAX = '\u{01114111}'
     const HEX_MAX_LEN: usize = 6; // char::MAX = '\u{10ffff}'
 
-    let restore = chars.clone();
-    {
-        if let Some('u') = chars.next()
-            && Some('{') == chars.next()
-        {
-            if chars.peek() == Some(&'0')
-                && chars.clone().take(DECIMAL_MAX_LEN + 1).any(|c| c == '}')
-            {
-                // decode as decimal number
-                let decimal: String = chars.take_while(|&c| c != '}').collect();
-                if let Ok(n) = decimal.parse::<u32>()
-                    && let Some(ch) = std::char::from_u32(n)
-                {
-                    return Some(ch);
-                }
-            } else if chars.clone().take(HEX_MAX_LEN + 1).any(|c| c == '}') {
-                // decode as hex number
-                let hex: String = chars.take_while(|&c| c != '}').collect();
-                if let Ok(val) = u32::from_str_radix(&hex, 16)
-                    && let Some(ch) = std::char::from_u32(val)
-                {
-                    return Some(ch);
+    let end = body.find('}')?;
+    let digits = &body[..end];
+    let code = if digits.starts_with('0') && end <= DECIMAL_MAX_LEN {
+        digits.parse::<u32>().ok()?
+    } else if end <= HEX_MAX_LEN {
+        u32::from_str_radix(digits, 16).ok()?
+    } else {
+        return None;
+    };
+    Some((char::from_u32(code)?, end + 1))
+}
+
+// Normalization policy: diagram cells and passwords are NFC, the form keyboards
+// produce on most platforms, so the same visible text gives the same wallets.
+// Cells of legacy diagram files are the exception, kept as typed for their wallets.
+// BIP39 passphrases are NFKD as the standard requires, so other wallets agree.
+
+/// NFC form of a password.
+pub fn nfc_secret(s: &str) -> SecretString {
+    // NFC expands a text at most threefold, reserve it to avoid copies by reallocation
+    let mut nfc = SecretString::new(String::with_capacity(s.len() * 3));
+    nfc.as_mut_string().extend(s.nfc());
+    nfc
+}
+
+/// NFKD form of a BIP39 passphrase.
+pub fn nfkd_secret(s: &str) -> SecretString {
+    // NFKD expands a text at most elevenfold in UTF-8
+    let mut nfkd = SecretString::new(String::with_capacity(s.len() * 11));
+    nfkd.as_mut_string().extend(s.nfkd());
+    nfkd
+}
+
+/// Characters of a text that a reader may not see or may take for others:
+/// invisible characters, mixed scripts and lookalikes of ascii characters.
+pub fn unicode_warnings(s: &str) -> Vec<String> {
+    let mut warnings = vec![];
+    if s.trim() != s {
+        warnings.push("has leading or trailing spaces".to_owned());
+    }
+    for g in s.graphemes(true) {
+        // joiners and variation selectors build emoji, they are part of the glyph
+        let emoji = g.chars().any(is_emoji);
+        for ch in g.chars() {
+            let escaped = unicode_encode(&ch.to_string());
+            if is_invisible(ch) && !(emoji && is_emoji_component(ch)) {
+                warnings.push(format!("has invisible character {escaped}"));
+            } else if !ch.is_ascii() {
+                let skeleton: String = skeleton(&ch.to_string()).collect();
+                let compatible: String = ch.to_string().nfkc().collect();
+                if skeleton.is_ascii() && skeleton.trim() != "" {
+                    warnings.push(format!("has {escaped} that looks like `{skeleton}`"));
+                } else if compatible.is_ascii() && compatible.trim() != "" {
+                    warnings.push(format!("has {escaped}, a variant of `{compatible}`"));
                 }
             }
         }
     }
-    *chars = restore;
-    None
+    if !s.is_single_script() {
+        warnings.push("mixes scripts".to_owned());
+    }
+    warnings.dedup();
+    warnings
+}
+
+/// Control, format and other default ignorable characters, and unusual spaces.
+fn is_invisible(ch: char) -> bool {
+    ch.is_control()
+        || matches!(ch,
+            '\u{a0}' | '\u{ad}' | '\u{34f}' | '\u{61c}' | '\u{115f}' | '\u{1160}'
+            | '\u{1680}' | '\u{17b4}' | '\u{17b5}' | '\u{180b}'..='\u{180f}'
+            | '\u{2000}'..='\u{200f}' | '\u{2028}'..='\u{202f}' | '\u{205f}'..='\u{206f}'
+            | '\u{3000}' | '\u{3164}' | '\u{fe00}'..='\u{fe0f}' | '\u{feff}' | '\u{ffa0}'
+            | '\u{fff0}'..='\u{fff8}' | '\u{1bca0}'..='\u{1bca3}' | '\u{1d173}'..='\u{1d17a}'
+            | '\u{e0000}'..='\u{e0fff}')
+}
+
+fn is_emoji(ch: char) -> bool {
+    matches!(ch, '\u{2600}'..='\u{27bf}' | '\u{2b00}'..='\u{2bff}' | '\u{1f000}'..='\u{1faff}')
+}
+
+/// Zero width joiner, variation selectors and tags inside emoji sequences.
+fn is_emoji_component(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{200d}' | '\u{fe0e}' | '\u{fe0f}' | '\u{e0020}'..='\u{e007f}'
+    )
+}
+
+/// Terminal columns of a text, counted by grapheme clusters.
+/// East Asian ambiguous characters are wide in a Chinese, Japanese or Korean locale.
+pub fn display_width(s: &str) -> usize {
+    let cjk = cjk_locale();
+    s.graphemes(true)
+        .map(|g| {
+            let width = match cjk {
+                true => g.width_cjk(),
+                false => g.width(),
+            };
+            // an emoji sequence joined by ZWJ is drawn as one wide glyph
+            match g.chars().nth(1) {
+                Some(_) => width.min(2),
+                None => width,
+            }
+        })
+        .sum()
+}
+
+fn cjk_locale() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|k| std::env::var(k).ok().filter(|v| !v.is_empty()))
+        .is_some_and(|v| ["zh", "ja", "ko"].iter().any(|l| v.starts_with(l)))
 }
 
 #[cfg(test)]
@@ -74,13 +279,93 @@ mod unicode_test {
             assert_eq!(unicode_decode(&escape), s.to_owned());
         }
         for s in KEEP_DATA {
-            assert_eq!(unicode_decode(&s), s.to_owned(), "{s}");
+            assert_eq!(unicode_decode(s), s.to_owned(), "{s}");
         }
         for (r, s) in SPECIAL_DATA {
-            assert_eq!(unicode_decode(&r), s.to_owned(), "{r}");
+            assert_eq!(unicode_decode(r), s.to_owned(), "{r}");
         }
         assert_ne!(unicode_decode(r"\u{10ffff}"), r"\u{10ffff}");
     }
+    #[test]
+    fn test_unicode_forms() {
+        const FORM_DATA: &[(&str, &str)] = &[
+            ("U+2248", "≈"),
+            ("U+1F511 x", "🔑 x"),
+            ("U+2248A", "\u{2248a}"),
+            ("U+20BF-TC", "₿-TC"),
+            ("U+20BFTC", "U+20BFTC"),
+            ("U+110000", "U+110000"),
+            ("&asymp;&#x2248;&#8776;", "≈≈≈"),
+            ("AT&amp;T", "AT&T"),
+            (r"\N{ALMOST EQUAL TO}", "≈"),
+            (r"\N{bitcoin sign}", "₿"),
+            (":key::+1:", "🔑👍"),
+            ("18:15:05", "18:15:05"),
+            ("U+12 U+D800", "U+12 U+D800"),
+            ("AT&T; &nosuch;", "AT&T; &nosuch;"),
+            (r"\N{NO SUCH NAME}", r"\N{NO SUCH NAME}"),
+            (":no_such_code:", ":no_such_code:"),
+        ];
+        for (input, output) in FORM_DATA {
+            assert_eq!(unicode_decode_forms(input), *output, "{input}");
+        }
+        // legacy files and prompts decode `\u{..}` only
+        for (input, _) in FORM_DATA {
+            assert_eq!(unicode_decode(input), *input, "{input}");
+        }
+        assert_eq!(unicode_decode(r"U+2248\u{2248}"), "U+2248≈");
+        assert_eq!(unicode_describe("₿TC"), r#"U+20BF BITCOIN SIGN, "TC""#);
+    }
+
+    #[test]
+    fn test_display_width() {
+        const WIDTH_DATA: &[(&str, usize)] = &[
+            ("abc", 3),
+            ("汉字", 4),
+            ("한국", 4),
+            ("\u{1f44d}\u{1f3fd}", 2),
+            ("\u{1f1ef}\u{1f1f5}", 2),
+            ("\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}", 2),
+            ("\u{2764}\u{fe0f}", 2),
+            ("e\u{301}", 1),
+            ("\u{ff71}", 1),
+        ];
+        for (s, width) in WIDTH_DATA {
+            assert_eq!(display_width(s), *width, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_unicode_warnings() {
+        const WARN_DATA: &[(&str, &str)] = &[
+            ("p\u{430}y", "has \\u{430} that looks like `a`"),
+            ("p\u{430}y", "mixes scripts"),
+            ("\u{ff11}\u{ff12}", "has \\u{ff11}, a variant of `1`"),
+            ("BT\u{200b}C", "has invisible character \\u{200b}"),
+            ("BTC ", "has leading or trailing spaces"),
+        ];
+        for (s, warning) in WARN_DATA {
+            let warnings = unicode_warnings(s);
+            assert!(warnings.iter().any(|w| w == warning), "{s}: {warnings:?}");
+        }
+        for s in [
+            "Satoshi",
+            "\u{20bf}TC",
+            "汉字かな",
+            "é",
+            "👨\u{200d}👩\u{200d}👧",
+            "\u{2764}\u{fe0f}",
+        ] {
+            assert!(
+                unicode_warnings(s).is_empty(),
+                "{s}: {:?}",
+                unicode_warnings(s)
+            );
+        }
+        assert_eq!(nfc_secret("e\u{301}").as_str(), "\u{e9}");
+        assert_eq!(nfkd_secret("\u{e9}\u{ff11}").as_str(), "e\u{301}1");
+    }
+
     const KEEP_DATA: &[&str] = &[
         r"a\u{1f6AM}123",
         r"a\u{FFFFFFFF}xxx",
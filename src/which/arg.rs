@@ -0,0 +1,97 @@
+use crate::diagram::LoadMatrix;
+use crate::utils::{ChildInput, PasswordInput, SecretString, SecretXpriv, nfc_secret};
+use artimonist::{ComplexDiagram, GenericDiagram, Matrix, SimpleDiagram, Xpriv};
+use std::str::FromStr;
+use zeroize::Zeroize;
+
+#[derive(clap::Parser, Debug)]
+pub struct WhichIndexCommand {
+    /// Diagram file or master key the child was generated from
+    #[clap(name = "DIAGRAM|MASTER_KEY")]
+    pub source: IndexSource,
+
+    /// Generated mnemonic, wallet address, private key, xprv or password, prompt if omitted
+    #[clap(name = "CHILD")]
+    pub child: Option<SecretString>,
+
+    /// Child input channels
+    #[clap(flatten)]
+    pub child_input: ChildInput,
+
+    /// The diagram is complex
+    #[clap(long)]
+    pub complex: bool,
+
+    /// Start index to scan
+    #[clap(short, long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..65536))]
+    pub index: u32,
+
+    /// Amount of indices to scan
+    #[clap(short = 'm', long, default_value_t = 1000, value_parser = clap::value_parser!(u32).range(1..65536))]
+    pub amount: u32,
+
+    /// Password as salt
+    #[clap(hide = true, long, env = "ARTIMONIST_PASSWORD", hide_env_values = true)]
+    pub password: Option<SecretString>,
+
+    /// Password input channels
+    #[clap(flatten)]
+    pub password_input: PasswordInput,
+}
+
+/// Root of the BIP85 children
+#[derive(Debug, Clone)]
+pub enum IndexSource {
+    /// Diagram file, with the password as salt
+    Diagram(String),
+    /// Master key in xprv format
+    Master(SecretXpriv),
+}
+
+impl IndexSource {
+    /// Master key of the source, with the password of a diagram as its salt.
+    pub fn master<F>(
+        &self,
+        complex: bool,
+        password: F,
+    ) -> anyhow::Result<(SecretXpriv, Option<SecretString>)>
+    where
+        F: FnOnce() -> anyhow::Result<SecretString>,
+    {
+        let path = match self {
+            IndexSource::Master(master) => return Ok((master.clone(), None)),
+            IndexSource::Diagram(path) => path,
+        };
+        let salt = nfc_secret(&password()?);
+        let master = match complex {
+            false => {
+                let mut diagram = SimpleDiagram(Matrix::<char, 7, 7>::from_file(path)?);
+                let master = diagram.bip32_master(salt.as_bytes());
+                diagram.0.zeroize();
+                master?
+            }
+            true => {
+                let mut diagram = ComplexDiagram(Matrix::<String, 7, 7>::from_file(path)?);
+                let master = diagram.bip32_master(salt.as_bytes());
+                diagram.0.zeroize();
+                master?
+            }
+        };
+        // encrypted private keys of the diagram are decrypted by the same password
+        Ok((SecretXpriv::new(master), Some(salt)))
+    }
+}
+
+impl FromStr for IndexSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("xprv") {
+            Ok(IndexSource::Master(SecretXpriv::new(Xpriv::from_str(s)?)))
+        } else if std::path::Path::new(s).exists() {
+            Ok(IndexSource::Diagram(s.to_owned()))
+        } else {
+            Err(anyhow::anyhow!("no diagram file or master key: {s}"))
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use super::arg::WhichIndexCommand;
+use crate::bip85::Bip85App;
+use crate::utils::{SecretInput, SecretString, inquire_child, inquire_password, nfkd_secret};
+use anyhow::bail;
+use artimonist::bitcoin::Address;
+use artimonist::{BIP38, BIP85, Language, Password, Xpriv};
+use std::str::FromStr;
+
+impl crate::Execute for WhichIndexCommand {
+    fn execute(&mut self) -> anyhow::Result<()> {
+        let child = self
+            .child_input
+            .resolve(self.child.as_ref(), inquire_child)?;
+        let (master, salt) = self.source.master(self.complex, || {
+            self.password_input
+                .resolve(self.password.as_ref(), || inquire_password(true))
+        })?;
+        let child = Child::parse(&child, salt.as_ref())?;
+        for index in self.index..self.index + self.amount {
+            if let Some(found) = child.generated(&master, index)? {
+                println!("Index: {index}");
+                println!("Generated: {found}");
+                return Ok(());
+            }
+        }
+        bail!(
+            "no BIP85 index in {}..{} generates the child, try other --index and --amount",
+            self.index,
+            self.index + self.amount
+        );
+    }
+}
+
+/// BIP85 child to look up, of the application its form tells
+enum Child {
+    /// Words of a mnemonic and the languages having them all
+    Mnemonic(Vec<SecretString>, Vec<Language>),
+    Address(String),
+    PrivateKey(SecretString),
+    Xpriv(SecretString),
+    Hex(SecretString),
+    Password(SecretString),
+}
+
+impl Child {
+    fn parse(text: &SecretString, password: Option<&SecretString>) -> anyhow::Result<Self> {
+        let text = text.as_str().trim();
+        let words: Vec<SecretString> = nfkd_secret(text)
+            .as_str()
+            .split_whitespace()
+            .map(|w| SecretString::new(w.to_lowercase()))
+            .collect();
+        if matches!(words.len(), 12 | 15 | 18 | 21 | 24) {
+            let languages: Vec<Language> = Language::all()
+                .iter()
+                .filter(|lang| words.iter().all(|w| lang.index_of(w.as_str()).is_some()))
+                .copied()
+                .collect();
+            if languages.is_empty() {
+                bail!("the mnemonic's words are not in a wordlist");
+            }
+            return Ok(Child::Mnemonic(words, languages));
+        }
+        if text.starts_with("xprv") {
+            return Ok(Child::Xpriv(SecretString::new(text.to_owned())));
+        }
+        if (32..=128).contains(&text.len())
+            && text.len().is_multiple_of(2)
+            && text.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return Ok(Child::Hex(SecretString::new(text.to_lowercase())));
+        }
+        if text.starts_with("6P") && text.chars().count() == 58 {
+            let Some(password) = password else {
+                bail!(
+                    "an encrypted private key is decrypted by the diagram's password, give its address instead"
+                );
+            };
+            let pk = SecretString::new(text.bip38_decrypt(password.as_str())?);
+            return Ok(Child::PrivateKey(pk));
+        }
+        if text.starts_with(['K', 'L', '5']) && text.chars().count() == 52 {
+            return Ok(Child::PrivateKey(SecretString::new(text.to_owned())));
+        }
+        if Address::from_str(text).is_ok() {
+            return Ok(Child::Address(text.to_owned()));
+        }
+        if (10..=86).contains(&text.chars().count()) {
+            return Ok(Child::Password(SecretString::new(text.to_owned())));
+        }
+        bail!(
+            "not a mnemonic, wallet address, private key, xprv, hex entropy or password of 10 to 86 characters"
+        );
+    }
+
+    /// What the child is, if generated at the index.
+    fn generated(&self, master: &Xpriv, index: u32) -> anyhow::Result<Option<String>> {
+        match self {
+            Child::Mnemonic(words, languages) => {
+                let count = words.len() as u32;
+                for &language in languages {
+                    let mnemonic =
+                        SecretString::new(master.bip85_mnemonic(language, count, index)?);
+                    if mnemonic
+                        .as_str()
+                        .split_whitespace()
+                        .eq(words.iter().map(|w| w.as_str()))
+                    {
+                        return Ok(Some(format!("mnemonic of {count} {language} words")));
+                    }
+                }
+                Ok(None)
+            }
+            Child::Address(addr) => {
+                let wif = master.bip85_wif(index)?;
+                drop(SecretString::new(wif.pk));
+                Ok((wif.addr == *addr).then(|| "wallet address".to_owned()))
+            }
+            Child::PrivateKey(pk) => {
+                let wif = master.bip85_wif(index)?;
+                let generated = SecretString::new(wif.pk);
+                Ok((generated.as_str() == pk.as_str())
+                    .then(|| format!("private key of {}", wif.addr)))
+            }
+            Child::Xpriv(xpriv) => {
+                let generated = SecretString::new(master.bip85_xpriv(index)?);
+                Ok((generated.as_str() == xpriv.as_str()).then(|| "master key xprv".to_owned()))
+            }
+            Child::Hex(hex) => {
+                let bytes = hex.as_str().len() as u32 / 2;
+                let generated = SecretString::new(master.bip85_hex(bytes, index)?);
+                Ok((generated.as_str() == hex.as_str())
+                    .then(|| format!("hex entropy of {bytes} bytes")))
+            }
+            Child::Password(pwd) => {
+                let length = pwd.as_str().chars().count();
+                let generated = [
+                    (
+                        "distinct",
+                        master
+                            .bip85_pwd(Password::Distinct, length, index)
+                            .map_err(Into::into),
+                    ),
+                    ("base64", master.bip85_base64(length as u32, index)),
+                    ("base85", master.bip85_base85(length as u32, index)),
+                    (
+                        "emoji",
+                        master
+                            .bip85_pwd(Password::Emoji, length, index)
+                            .map_err(Into::into),
+                    ),
+                    (
+                        "mixture",
+                        master
+                            .bip85_pwd(Password::Mixture, length, index)
+                            .map_err(Into::into),
+                    ),
+                ];
+                for (name, generated) in generated {
+                    // lengths out of an application's range are not of it
+                    let Ok(generated) = generated.map(SecretString::new) else {
+                        continue;
+                    };
+                    if generated.as_str() == pwd.as_str() {
+                        return Ok(Some(format!("{name} password of {length} characters")));
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+}
@@ -33,3 +33,22 @@ fn test_derive_bip32() {
     let output = cli_derive!("m/0/0", XPUB);
     assert_eq!(output, include_str!("bip32/bip32_xpub"));
 }
+
+#[test]
+fn test_derive_bip32_passphrase() {
+    let output = cli_derive!(
+        "m/0/0 --no-passphrase",
+        "播 画 紫 巴 云 样 垫 粗 鲁 爆 罩 酶"
+    );
+    assert_eq!(output, include_str!("bip32/bip32_no_passphrase"));
+}
+
+#[test]
+fn test_derive_bip32_normalization() {
+    // BIP39 test vector: composed mnemonic and passphrase are taken in NFKD
+    let output = cli_derive!(
+        "m/0 --passphrase ㍍ガバヴァぱばぐゞちぢ十人十色",
+        "あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あおぞら"
+    );
+    assert!(output.starts_with("Master key: xprv9s21ZrQH143K258jAiWPAM6JYT9hLA91MV3AZUKfxmLZJCjCHeSjBvMbDy8C1mJ2FL5ytExyS97FAe6pQ6SD5Jt9SwHaLorA8i5Eojokfo1\n"));
+}
@@ -81,3 +81,243 @@ fn test_derive_master() {
     let result = cli_derive!("-a 1111 -i 100 -m 5 --m35 --detail", MASTER);
     assert_eq!(result, include_str!("derive/master_m35"));
 }
+
+#[test]
+fn test_derive_export() {
+    const MASTER: &str = "xprv9s21ZrQH143K4UoTfggaDMmCkfpe9UoALJsg38fDuE5aEmiP9eub61MJmkMfKVjRdM38StnFGo3nb4tGgXZ91LeZZFsG11u7paJzCk9memZ";
+    const MNEMONIC: &str =
+        "級 蠟 棒 氣 調 乏 島 陶 勞 量 強 給 電 鑽 路 婦 趙 撥 士 殿 什 遠 亞 互";
+
+    let result = cli_derive!("--bip49 -m 5 --export-keys electrum --yes", MASTER);
+    assert_eq!(result, include_str!("derive/export_electrum"));
+
+    // header line contains the program version
+    let result = cli_derive!("--bip84 -a 1 -m 5 --export-keys core --yes", MASTER);
+    let (header, body) = result.split_once('\n').unwrap();
+    assert!(header.starts_with("# Wallet dump created by Artimonist "));
+    assert_eq!(body, include_str!("derive/export_core"));
+
+    let result = cli_derive!("--bip44 -i 2048 -m 5 --export-keys wif --yes", MASTER);
+    assert_eq!(result, include_str!("derive/export_wif"));
+
+    // password is still the bip39 passphrase of mnemonic
+    let result = cli_derive!("--bip84 -i 10 -m 5 --export-keys electrum --yes", MNEMONIC);
+    assert_eq!(result, include_str!("derive/export_mnemonic"));
+}
+
+macro_rules! cli_derive_error {
+    ($content:expr, $args:expr, $key:expr) => {{
+        let args = $args.split_whitespace().collect::<Vec<_>>();
+        let mut cmd = Command::cargo_bin("artimonist").unwrap();
+        cmd.arg("derive")
+            .args(&["--password", "123456"])
+            .args(&args)
+            .arg($key)
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains($content));
+    }};
+}
+
+#[test]
+fn test_derive_export_error() {
+    const MASTER: &str = "xprv9s21ZrQH143K4UoTfggaDMmCkfpe9UoALJsg38fDuE5aEmiP9eub61MJmkMfKVjRdM38StnFGo3nb4tGgXZ91LeZZFsG11u7paJzCk9memZ";
+
+    cli_derive_error!("--export-keys [<FORMAT>]", "--yes", MASTER);
+    cli_derive_error!(
+        "`--export-keys <electrum|core|wif>`",
+        "--bip84 --export-keys --yes",
+        MASTER
+    );
+    cli_derive_error!(
+        "'--m23' cannot be used",
+        "--m23 --export-keys wif --yes",
+        MASTER
+    );
+    cli_derive_error!(
+        "'--detail' cannot be used",
+        "--detail --export-keys wif --yes",
+        MASTER
+    );
+}
+
+#[test]
+fn test_derive_passphrase() {
+    const MNEMONIC: &str =
+        "級 蠟 棒 氣 調 乏 島 陶 勞 量 強 給 電 鑽 路 婦 趙 撥 士 殿 什 遠 亞 互";
+
+    // explicit passphrase and encryption password equal to legacy password
+    let output = cli_derive!(
+        "--passphrase 123456 --encrypt-with 123456 -i 10 -m 5",
+        MNEMONIC
+    );
+    assert_eq!(output, include_str!("derive/mnemonic_bip49"));
+
+    // standard wallet with encrypted private keys
+    let output = cli_derive!("--no-passphrase -m 3", MNEMONIC);
+    assert_eq!(output, include_str!("derive/mnemonic_no_passphrase"));
+}
+
+#[test]
+fn test_derive_secret_input() {
+    const MNEMONIC: &str =
+        "級 蠟 棒 氣 調 乏 島 陶 勞 量 強 給 電 鑽 路 婦 趙 撥 士 殿 什 遠 亞 互";
+
+    // mnemonic from stdin
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    let output = cmd
+        .args([
+            "derive",
+            "--password",
+            "123456",
+            "-i",
+            "10",
+            "-m",
+            "5",
+            "--key-stdin",
+        ])
+        .write_stdin(format!("{MNEMONIC}\n"))
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert_eq!(output.stdout, include_bytes!("derive/mnemonic_bip49"));
+
+    // mnemonic from environment, passphrase from file, password from stdin
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    let output = cmd
+        .current_dir("tests/derive")
+        .env("ARTIMONIST_KEY", MNEMONIC)
+        .args([
+            "derive",
+            "-i",
+            "10",
+            "-m",
+            "5",
+            "--passphrase-file",
+            "password",
+        ])
+        .arg("--encrypt-with-stdin")
+        .write_stdin("123456\n")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert_eq!(output.stdout, include_bytes!("derive/mnemonic_bip49"));
+
+    // stdin supplies only one secret
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args([
+        "derive",
+        "--key-stdin",
+        "--passphrase-stdin",
+        "--no-passphrase",
+    ])
+    .assert()
+    .failure();
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args([
+        "derive",
+        "--key-stdin",
+        "--passphrase-stdin",
+        "--encrypt-with",
+        "1",
+    ])
+    .write_stdin(MNEMONIC)
+    .assert()
+    .failure()
+    .stderr(predicates::str::contains(
+        "stdin can only supply one secret",
+    ));
+
+    // closed, reserved and negative file descriptors
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args(["derive", "--key-fd", "47"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("file descriptor 47 is not open"));
+    for fd in ["--key-fd=2", "--key-fd=-1"] {
+        let mut cmd = Command::cargo_bin("artimonist").unwrap();
+        cmd.args(["derive", fd]).assert().code(2);
+    }
+}
+
+#[test]
+fn test_derive_no_input() {
+    const MASTER: &str = "xprv9s21ZrQH143K4UoTfggaDMmCkfpe9UoALJsg38fDuE5aEmiP9eub61MJmkMfKVjRdM38StnFGo3nb4tGgXZ91LeZZFsG11u7paJzCk9memZ";
+
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args(["derive", "--no-input"])
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains("--key-stdin"));
+
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args(["derive", "--no-input", MASTER])
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains("missing encryption password"));
+
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args(["derive", "--no-input", "--export-keys", "wif", MASTER])
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains("--yes"));
+}
+
+#[test]
+fn test_derive_nostr() {
+    // test vector of NIP-06
+    const MNEMONIC: &str =
+        "leader monkey parrot ring guide accident before fence cannon height naive bean";
+
+    let output = cli_derive!("--nostr --no-passphrase -m 1 --export-keys --yes", MNEMONIC);
+    assert_eq!(
+        output,
+        "[m/44'/1237'/0'/0/0]: npub1zutzeysacnf9rru6zqwmxd54mud0k44tst6l70ja5mhv8jjumytsd2x7nu, 17162c921dc4d2518f9a101db33695df1afb56ab82f5ff3e5da6eec3ca5cd917, nsec10allq0gjx7fddtzef0ax00mdps9t2kmtrldkyjfs8l5xruwvh2dq0lhhkp, 7f7ff03d123792d6ac594bfa67bf6d0c0ab55b6b1fdb6249303fe861f1ccba9a\n"
+    );
+
+    // secret keys encrypted by NIP-49 of random salts
+    let output = cli_derive!("--nostr --no-passphrase -a 1 -m 2", MNEMONIC);
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("[m/44'/1237'/1'/0/0]: npub1"));
+    assert!(lines[1].starts_with("[m/44'/1237'/1'/0/1]: npub1"));
+    assert!(lines.iter().all(|ln| ln.contains(", ncryptsec1")));
+
+    let master = "xprv9s21ZrQH143K4UoTfggaDMmCkfpe9UoALJsg38fDuE5aEmiP9eub61MJmkMfKVjRdM38StnFGo3nb4tGgXZ91LeZZFsG11u7paJzCk9memZ";
+    cli_derive_error!("'--nostr' cannot be used", "--nostr --m23", master);
+    cli_derive_error!(
+        "exported by `--export-keys` alone",
+        "--nostr --export-keys wif --yes",
+        master
+    );
+}
+
+#[test]
+fn test_derive_evm() {
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    let output = cli_derive!("--evm --no-passphrase -m 2 --export-keys --yes", MNEMONIC);
+    assert_eq!(
+        output,
+        "[m/44'/60'/0'/0/0]: 0x9858EfFD232B4033E47d90003D41EC34EcaEda94, 0x1ab42cc412b618bdea3a599e3c9bae199ebf030895b039e9db1e30dafb12b727\n\
+         [m/44'/60'/0'/0/1]: 0x6Fac4D18c912343BF86fa7049364Dd4E424Ab9C0, 0x9a983cb3d832fbde5ab49d692b7a8bf5b5d232479c99333d0fc8e1d21f1b55b6\n"
+    );
+
+    // private keys encrypted as keystore files of random salts
+    let output = cli_derive!("--evm --no-passphrase -m 1", MNEMONIC);
+    assert!(output.starts_with(
+        "[m/44'/60'/0'/0/0]: 0x9858EfFD232B4033E47d90003D41EC34EcaEda94, {\"address\":\"9858effd232b4033e47d90003d41ec34ecaeda94\",\"crypto\":{\"cipher\":\"aes-128-ctr\","
+    ));
+    assert!(output.trim_end().ends_with(",\"version\":3}"));
+
+    let master = "xprv9s21ZrQH143K4UoTfggaDMmCkfpe9UoALJsg38fDuE5aEmiP9eub61MJmkMfKVjRdM38StnFGo3nb4tGgXZ91LeZZFsG11u7paJzCk9memZ";
+    cli_derive_error!("'--evm' cannot be used", "--evm --m35", master);
+    cli_derive_error!("'--evm' cannot be used", "--evm --nostr", master);
+    cli_derive_error!(
+        "exported by `--export-keys` alone",
+        "--evm --export-keys core --yes",
+        master
+    );
+}
@@ -1,6 +1,7 @@
 #![cfg(not(feature = "testnet"))]
 
 use assert_cmd::Command;
+use predicates::prelude::*;
 
 macro_rules! cli_execute {
     ($args:literal) => {{
@@ -11,6 +12,7 @@ macro_rules! cli_execute {
             .args(&args)
             .args(&["--password", "123456"])
             .args(&["--language", "english"])
+            .arg("--accept-weak")
             .assert()
             .success()
             .get_output()
@@ -58,4 +60,293 @@ fn test_diagram_complex() {
 
     let result = cli_execute!("complex -f complex_unicode.art --unicode -m 3");
     assert_eq!(result, include_str!("diagram/complex_unicode"));
+
+    // characters by U+ notation, entities, names and shortcodes
+    let result = cli_execute!("complex -f complex_forms.art --unicode -m 3");
+    assert_eq!(result, include_str!("diagram/complex_unicode"));
+
+    // legacy files keep such text literally, and the wallets of earlier versions
+    let result = cli_execute!("complex -f complex_legacy_forms.art --xprv");
+    assert!(result.contains("(0): xprv9s21ZrQH143K3PAXLiq9YxoCuLqDsbuUa1bWBQFAafxU7DrGN3vSni6PpMfkZ2TtdB1YMQ3TXN9AKK2DzQGNpqUumP9tWt7KZuWb9ofnFQ7\n"));
+}
+
+#[test]
+fn test_diagram_password_input() {
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    let output = cmd
+        .current_dir("tests/diagram")
+        .args([
+            "simple",
+            "-f",
+            "simple.art",
+            "--xprv",
+            "-m",
+            "20",
+            "--password-stdin",
+            "--accept-weak",
+        ])
+        .write_stdin("123456\n")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert_eq!(output.stdout, include_bytes!("diagram/simple_xprv"));
+
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    let output = cmd
+        .current_dir("tests/diagram")
+        .env("ARTIMONIST_PASSWORD", "123456")
+        .args([
+            "simple",
+            "-f",
+            "simple.art",
+            "--xprv",
+            "-m",
+            "20",
+            "--accept-weak",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert_eq!(output.stdout, include_bytes!("diagram/simple_xprv"));
+}
+
+#[test]
+fn test_diagram_no_input() {
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args(["simple", "--no-input", "--password", "123456"])
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains("--file"));
+
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.current_dir("tests/diagram")
+        .args([
+            "complex",
+            "-f",
+            "complex.art",
+            "--no-input",
+            "--password",
+            "123456",
+        ])
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains("--language"));
+}
+
+#[test]
+fn test_diagram_format() {
+    // version 1 files give the same results as legacy files
+    let result = cli_execute!("simple -f simple_v1.art -i 1000 -m 10");
+    assert_eq!(result, include_str!("diagram/simple_default"));
+
+    let result = cli_execute!("complex -f complex_v1.art -i 500 -m 20");
+    assert_eq!(result, include_str!("diagram/complex_default"));
+}
+
+#[test]
+fn test_diagram_format_error() {
+    macro_rules! cli_format_error {
+        ($cmd:literal, $file:literal, $msg:literal) => {
+            let mut cmd = Command::cargo_bin("artimonist").unwrap();
+            cmd.current_dir("tests/diagram")
+                .args([$cmd, "-f", $file, "--password", "123456"])
+                .args(["--language", "english"])
+                .assert()
+                .failure()
+                .stderr(predicates::str::contains($msg));
+        };
+    }
+    cli_format_error!(
+        "simple",
+        "error_extra_cell.art",
+        "line 5, column 29: extra cell"
+    );
+    cli_format_error!(
+        "complex",
+        "error_long_cell.art",
+        "line 3, column 9: complex cell has 28 characters"
+    );
+    cli_format_error!(
+        "complex",
+        "error_checksum.art",
+        "line 4, column 1: checksum mismatch"
+    );
+    cli_format_error!(
+        "simple",
+        "error_grapheme.art",
+        "line 4, column 7: simple cell is a character of 2 code points and would be truncated"
+    );
+    cli_format_error!("complex", "simple_v1.art", "use the `simple` command");
+}
+
+#[test]
+fn test_diagram_save() {
+    let dir = std::env::temp_dir().join(format!("artimonist-save-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for (kind, file, saved, expected) in [
+        (
+            "simple",
+            "simple_unicode.art",
+            include_str!("diagram/simple_unicode_v1.art"),
+            include_str!("diagram/simple_unicode"),
+        ),
+        (
+            "complex",
+            "complex_unicode.art",
+            include_str!("diagram/complex_unicode_v1.art"),
+            include_str!("diagram/complex_unicode"),
+        ),
+    ] {
+        let path = dir.join(file);
+        let path = path.to_str().unwrap();
+        let mut cmd = Command::cargo_bin("artimonist").unwrap();
+        cmd.current_dir("tests/diagram")
+            .args([kind, "-f", file, "--save", path, "-m", "0", "--accept-weak"])
+            .args(["--password", "123456", "--language", "english"])
+            .assert()
+            .success()
+            .stderr(predicates::str::contains("saved in plaintext"));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), saved);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // saved file gives the same results
+        let mut cmd = Command::cargo_bin("artimonist").unwrap();
+        let output = cmd
+            .args([kind, "-f", path, "--unicode", "--accept-weak"])
+            .args(["--password", "123456", "--language", "english"])
+            .args(if kind == "simple" {
+                ["-i", "200"]
+            } else {
+                ["-m", "3"]
+            })
+            .assert()
+            .success()
+            .get_output()
+            .clone();
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+
+        // existing files are not overwritten
+        let mut cmd = Command::cargo_bin("artimonist").unwrap();
+        cmd.args([kind, "-f", path, "--save", path, "--accept-weak"])
+            .args(["--password", "123456", "--language", "english"])
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("cannot create diagram file"));
+    }
+
+    // refused diagrams are not saved
+    let refused = dir.join("refused.art");
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.current_dir("tests/diagram")
+        .args([
+            "simple",
+            "-f",
+            "simple.art",
+            "--save",
+            refused.to_str().unwrap(),
+        ])
+        .args(["--password", "123456", "--language", "english"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("too weak"));
+    assert!(!refused.exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_diagram_strength() {
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.current_dir("tests/diagram")
+        .args(["simple", "-f", "simple.art", "--password", "123456"])
+        .args(["--language", "english"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Strength: weak"))
+        .stderr(predicates::str::contains("--accept-weak"));
+
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.current_dir("tests/diagram")
+        .args(["complex", "-f", "complex.art", "--password", "123456"])
+        .args(["--language", "english", "-m", "0"])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "Warning: cell 1,4 is a dictionary word",
+        ))
+        .stderr(predicates::str::contains("Strength: strong"));
+}
+
+#[test]
+fn test_diagram_normalization() {
+    // decomposed cells give the same diagram as composed ones
+    let composed = cli_execute!("complex -f complex_nfc.art -m 3");
+    let decomposed = cli_execute!("complex -f complex_nfd.art -m 3");
+    assert_eq!(composed, decomposed);
+
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.current_dir("tests/diagram")
+        .args(["complex", "-f", "complex_nfd.art", "--password", "123456"])
+        .args(["--language", "english", "--accept-weak", "-m", "0"])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            r"Warning: cell 2,6 `p\u{430}y` has \u{430} that looks like `a`, mixes scripts",
+        ))
+        .stderr(predicates::str::contains(
+            r"Warning: cell 4,3 `\u{ff11}\u{ff12}` has \u{ff11}, a variant of `1`",
+        ))
+        .stderr(predicates::str::contains(
+            r"Warning: cell 7,1 `BT\u{200b}C` has invisible character \u{200b}",
+        ))
+        .stderr(predicates::str::contains("cell 1,2").not());
+
+    // legacy files keep decomposed cells as typed, and the wallets of earlier versions
+    let result = cli_execute!("complex -f complex_legacy_nfd.art --xprv");
+    assert!(result.contains("(0): xprv9s21ZrQH143K4P4z3vXxXjQzBtFULz6o1xaH9nCaom2hx7n9ogikax6Fh4XRFLYFFoGwSqDcYDYqSpBmSLtqBiKUdEE2cae2AMq7Hkqc7ET\n"));
+
+    // a file of format 1 would compose them, so it is not saved
+    let dir = std::env::temp_dir().join(format!("artimonist-nfd-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("nfd.art");
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.current_dir("tests/diagram")
+        .args([
+            "complex",
+            "-f",
+            "complex_legacy_nfd.art",
+            "--password",
+            "123456",
+        ])
+        .args(["--accept-weak", "--xprv", "--save"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cell 2,4 is not in NFC"));
+    assert!(!path.exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_diagram_twice() {
+    // a diagram file is not entered twice
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.current_dir("tests/diagram")
+        .args(["simple", "-f", "simple.art", "--twice"])
+        .assert()
+        .code(2);
+
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args(["simple", "--twice", "--no-input"])
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains("missing diagram"));
 }
@@ -0,0 +1,86 @@
+#![cfg(not(feature = "testnet"))]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Master key of the BIP85 test vectors
+const MASTER: &str = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+
+macro_rules! cli_pwd {
+    ($($arg:expr),*) => {{
+        let mut cmd = Command::cargo_bin("artimonist").unwrap();
+        cmd.arg("pwd")
+            $(.arg($arg))*
+            .assert()
+    }};
+}
+
+#[test]
+fn test_pwd_site() {
+    cli_pwd!(MASTER, "--site", "payroll.example.com")
+        .success()
+        .stdout(predicate::str::contains("Index: 1963747398"))
+        .stdout(predicate::str::contains("Password: nG47Pe#$rDrrrs$xbM9H"));
+    // the label of an url is its host
+    cli_pwd!(
+        MASTER,
+        "--site",
+        "HTTPS://www.Payroll.example.com/login",
+        "--counter",
+        "2",
+        "--length",
+        "24"
+    )
+    .success()
+    .stdout(predicate::str::contains("Site: payroll.example.com"))
+    .stdout(predicate::str::contains(
+        "Password: h4n4*hYaEDw#ebVtT&122jYe",
+    ));
+
+    cli_pwd!(MASTER, "--site", "bank", "--length", "12")
+        .failure()
+        .stderr(predicate::str::contains("20 to 86 characters"));
+}
+
+#[test]
+fn test_pwd_sites() {
+    let sites = std::env::temp_dir().join(format!("pwd-{}.sites", std::process::id()));
+    cli_pwd!(MASTER, "--site", "payroll.example.com", "--sites", &sites).success();
+    cli_pwd!(
+        MASTER,
+        "--site",
+        "payroll.example.com",
+        "--sites",
+        &sites,
+        "--rotate",
+        "--length",
+        "24"
+    )
+    .success()
+    .stdout(predicate::str::contains("Counter: 2"))
+    .stdout(predicate::str::contains(
+        "Password: h4n4*hYaEDw#ebVtT&122jYe",
+    ));
+
+    // labels and counters only, never passwords
+    let text = std::fs::read_to_string(&sites).unwrap();
+    assert!(
+        text.ends_with("\n2 24 distinct payroll.example.com\n"),
+        "{text}"
+    );
+    assert!(!text.contains("h4n4"));
+
+    // the stored counter and length are used
+    cli_pwd!(MASTER, "--site", "payroll.example.com", "--sites", &sites)
+        .success()
+        .stdout(predicate::str::contains("Counter: 2"));
+    cli_pwd!(MASTER, "--list", "--sites", &sites)
+        .success()
+        .stdout(predicate::str::contains(
+            "payroll.example.com #2: h4n4*hYaEDw#ebVtT&122jYe",
+        ));
+    cli_pwd!(MASTER, "--site", "bank", "--sites", &sites, "--rotate")
+        .failure()
+        .stderr(predicate::str::contains("no site `bank` to rotate"));
+    std::fs::remove_file(&sites).unwrap();
+}
@@ -0,0 +1,229 @@
+#![cfg(not(feature = "testnet"))]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Mnemonic at index 1003 of `tests/diagram/simple.art`
+const MNEMONIC: &str = "fantasy transfer push total curve silver dash worth amused possible wet movie obey double picture fluid coffee mobile drop power detect art magnet inmate";
+
+macro_rules! cli_recover {
+    ($($arg:expr),*) => {{
+        let mut cmd = Command::cargo_bin("artimonist").unwrap();
+        cmd.current_dir("tests/recover")
+            .arg("recover-diagram")
+            $(.arg($arg))*
+            .args(["--password", "123456"])
+            .assert()
+    }};
+}
+
+#[test]
+fn test_recover_diagram() {
+    // `O` typed for `0`
+    cli_recover!("simple_typo.art", "--fingerprint", "4001daed")
+        .success()
+        .stdout(predicate::str::contains("cell 1,7 `O` -> `0`"))
+        .stdout(predicate::str::contains(
+            "Fingerprint: 4001daed (autumn opera father)",
+        ));
+
+    cli_recover!("simple_typo.art", "--mnemonic", MNEMONIC, "-i", "1000")
+        .success()
+        .stdout(predicate::str::contains("cell 1,7 `O` -> `0`"));
+
+    cli_recover!("../diagram/simple.art", "--fingerprint", "4001daed")
+        .success()
+        .stdout(predicate::str::contains("as remembered matches"));
+}
+
+#[test]
+fn test_recover_diagram_input() {
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.current_dir("tests/recover")
+        .args([
+            "recover-diagram",
+            "simple_typo.art",
+            "--fingerprint",
+            "4001daed",
+            "--no-input",
+        ])
+        .assert()
+        .code(3);
+
+    cli_recover!(
+        "simple_typo.art",
+        "--fingerprint",
+        "4001daed",
+        "--address",
+        "3MqiYB6domzaqzd46b2EX3Z5bzKAJn2ysa"
+    )
+    .code(2);
+
+    cli_recover!(
+        "simple_typo.art",
+        "--fingerprint",
+        "4001daed",
+        "--candidates",
+        "candidates_invalid"
+    )
+    .failure()
+    .stderr(predicate::str::contains(
+        "line 2: simple cell must be one character",
+    ));
+}
+
+/// Mnemonic of `tests/derive`, with the third word missing
+const PARTIAL: &str = "級 蠟 ? 氣 調 乏 島 陶 勞 量 強 給 電 鑽 路 婦 趙 撥 士 殿 什 遠 亞 互";
+const RECOVERED: &str = "級 蠟 棒 氣 調 乏 島 陶 勞 量 強 給 電 鑽 路 婦 趙 撥 士 殿 什 遠 亞 互";
+
+macro_rules! cli_recover_mnemonic {
+    ($($arg:expr),*) => {{
+        let mut cmd = Command::cargo_bin("artimonist").unwrap();
+        cmd.arg("recover-mnemonic")
+            $(.arg($arg))*
+            .assert()
+    }};
+}
+
+#[test]
+fn test_recover_mnemonic() {
+    // candidates with a valid checksum
+    cli_recover_mnemonic!(PARTIAL)
+        .success()
+        .stdout(predicate::str::contains(RECOVERED))
+        .stderr(predicate::str::contains("2048 phrases"));
+
+    let address = "bc1q7l40tg4ead26y4mkfjzn7gu6wvw9zhhdvpl33d";
+    cli_recover_mnemonic!(PARTIAL, "--address", address, "--passphrase", "123456")
+        .success()
+        .stdout(format!(
+            "Recovered mnemonic: {RECOVERED}\nWallet path: m/84'/0'/0'/0/2\n"
+        ));
+
+    let xpub = "xpub6DR5Z1g6cZ8qNpwt5bSKiVgm8GiFJsUST5kiWdXE6bF4LkdWiysFKdHSr6ZH73jHfNVmzC7UdW3MsKKQfxt76McdY6rzyEfa1DJSsYSrae6";
+    cli_recover_mnemonic!(PARTIAL, "--xpub", xpub, "--passphrase", "123456")
+        .success()
+        .stdout(predicate::str::contains("Wallet path: m/44'/0'/0'"));
+
+    // another passphrase generates other wallets
+    cli_recover_mnemonic!(PARTIAL, "--address", address, "--no-passphrase")
+        .failure()
+        .stderr(predicate::str::contains("no phrase generates the wallet"));
+}
+
+#[test]
+fn test_recover_mnemonic_words() {
+    const MNEMONIC: &str = "lake album jump occur hedgehog fantasy drama sauce oyster velvet gadget control behave hamster begin";
+
+    // misspelled and swapped words
+    cli_recover_mnemonic!(MNEMONIC.replace("velvet", "vlevet"))
+        .success()
+        .stdout(predicate::str::contains(MNEMONIC))
+        .stderr(predicate::str::contains("word 10 `vlevet` may be: velvet"));
+    cli_recover_mnemonic!(MNEMONIC.replace("lake album", "album lake"), "--swapped")
+        .success()
+        .stdout(predicate::str::contains(MNEMONIC));
+
+    cli_recover_mnemonic!("lake album ? occur")
+        .failure()
+        .stderr(predicate::str::contains(
+            "a mnemonic has 12, 15, 18, 21 or 24 words, found 4",
+        ));
+}
+
+/// Mnemonic of BIP39 test vectors, with passphrase `Tr3zor` in the tests
+const PASSPHRASE_MNEMONIC: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+macro_rules! cli_recover_password {
+    ($($arg:expr),*) => {{
+        let mut cmd = Command::cargo_bin("artimonist").unwrap();
+        cmd.current_dir("tests/recover")
+            .arg("recover-password")
+            $(.arg($arg))*
+            .assert()
+    }};
+}
+
+#[test]
+fn test_recover_password() {
+    // `trezor` in leetspeak and capitalized
+    cli_recover_password!(
+        PASSPHRASE_MNEMONIC,
+        "--wordlist",
+        "passwords",
+        "--rules",
+        "leet,case",
+        "--address",
+        "bc1q9zzd05mq8znt63lmkdrmer49vjqycx0qthjf53"
+    )
+    .success()
+    .stdout(predicate::str::contains("Recovered password: Tr3zor"))
+    .stdout(predicate::str::contains("Wallet path: m/84'/0'/0'/0/1"));
+
+    // key encrypted by `P4$s`, no diagram or mnemonic needed
+    cli_recover_password!(
+        "--wordlist",
+        "passwords",
+        "--rules",
+        "leet,case",
+        "--bip38",
+        "6PYMn8XFbH8bP4vBh1PxVEahu1KW1dP8qEUpZ9d55sGSiXWke7mA5DNhu9"
+    )
+    .success()
+    .stdout(predicate::str::contains("Recovered password: P4$s"));
+
+    // salt `123456` of the diagram
+    cli_recover_password!(
+        "../diagram/simple.art",
+        "--wordlist",
+        "salts",
+        "--fingerprint",
+        "4001daed",
+        "--threads",
+        "2"
+    )
+    .success()
+    .stdout(predicate::str::contains("Recovered password: 123456"))
+    .stdout(predicate::str::contains(
+        "Fingerprint: 4001daed (autumn opera father)",
+    ));
+
+    cli_recover_password!("--wordlist", "passwords", "--fingerprint", "4001daed").code(2);
+}
+
+#[test]
+fn test_recover_password_checkpoint() {
+    let checkpoint =
+        std::env::temp_dir().join(format!("recover-{}.checkpoint", std::process::id()));
+    let recover = |rules: &str| {
+        cli_recover_password!(
+            PASSPHRASE_MNEMONIC,
+            "--wordlist",
+            "passwords",
+            "--rules",
+            rules,
+            "--address",
+            "bc1q9zzd05mq8znt63lmkdrmer49vjqycx0qthjf53",
+            "--checkpoint",
+            &checkpoint
+        )
+    };
+    // not found, all candidates are checked by the checkpoint
+    recover("case")
+        .failure()
+        .stderr(predicate::str::contains("no candidate password"));
+    let text = std::fs::read_to_string(&checkpoint).unwrap();
+    assert!(text.contains("checked = 9\n"), "{text}");
+    recover("case")
+        .failure()
+        .stderr(predicate::str::contains("all 9 candidates are checked"));
+    recover("digits")
+        .failure()
+        .stderr(predicate::str::contains("of another search"));
+
+    // found, the checkpoint is removed
+    std::fs::remove_file(&checkpoint).unwrap();
+    recover("leet,case").success();
+    assert!(!checkpoint.exists());
+}
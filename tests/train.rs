@@ -0,0 +1,87 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+#[test]
+fn test_train_record() {
+    let dir = std::env::temp_dir().join(format!("artimonist-train-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let record = dir.join("complex.train");
+    let record = record.to_str().unwrap();
+    let _ = std::fs::remove_file(record);
+
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.current_dir("tests/diagram")
+        .args([
+            "train",
+            "new",
+            record,
+            "--complex",
+            "--from",
+            "complex_v1.art",
+            "--password",
+            "123456",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("keep it private"));
+
+    // the record keeps row hashes only
+    let text = std::fs::read_to_string(record).unwrap();
+    assert!(
+        text.contains("type = complex")
+            && text.lines().filter(|l| l.starts_with("row = ")).count() == 7
+    );
+    assert!(!text.contains("BTC") && !text.contains("Satoshi"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(record).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // an existing record is never overwritten
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.current_dir("tests/diagram")
+        .args(["train", "new", record, "--from", "simple_v1.art"])
+        .args(["--password", "123456"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot create training record"));
+
+    // the record is bound to the diagram password
+    let other = dir.join("simple.train");
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.current_dir("tests/diagram")
+        .args([
+            "train",
+            "new",
+            other.to_str().unwrap(),
+            "--from",
+            "simple_v1.art",
+        ])
+        .arg("--no-input")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("--password-stdin"));
+    assert!(!other.exists());
+
+    // recall of past quizzes
+    let quizzes = "quiz = 1700000000 +++++++\nquiz = 1700086400 ...-...\n";
+    std::fs::write(record, text + quizzes).unwrap();
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args(["train", "stats", record])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("complex diagram: 2 quizzes"))
+        .stdout(predicate::str::contains("Row 4: correct 1 of the last 2"))
+        .stdout(predicate::str::contains("Full diagram: correct 1 of 1"))
+        .stdout(predicate::str::contains("Practice next: row 4\n"));
+
+    // a quiz needs the terminal
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args(["train", "quiz", record, "--no-input"])
+        .assert()
+        .code(3);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
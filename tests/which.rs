@@ -0,0 +1,87 @@
+#![cfg(not(feature = "testnet"))]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+macro_rules! cli_which {
+    ($($arg:expr),*) => {{
+        let mut cmd = Command::cargo_bin("artimonist").unwrap();
+        cmd.current_dir("tests/diagram")
+            .arg("which-index")
+            $(.arg($arg))*
+            .args(["--password", "123456"])
+            .assert()
+    }};
+}
+
+#[test]
+fn test_which_index() {
+    // children at index 5 of `simple.art`
+    cli_which!(
+        "simple.art",
+        "うおざ ひいき るいじ ちかい さくし ごまあぶら きおう たなばた きぬごし てぬぐい なのか かあつ"
+    )
+    .success()
+    .stdout(predicate::str::contains("Index: 5"))
+    .stdout(predicate::str::contains("mnemonic of 12 Japanese words"));
+
+    cli_which!(
+        "simple.art",
+        "6PYKnmJG5BaJWmF31MXUg8YqPZnGF49NxvtdtwDoyhjwrva4gqFCjXmG1J"
+    )
+    .success()
+    .stdout(predicate::str::contains(
+        "private key of 3HVJP9w1J4veu74PcF9UFAJRU9n2wUGEVB",
+    ));
+
+    cli_which!("simple.art", "o*2rz%zWnuTj@d*v#Awm")
+        .success()
+        .stdout(predicate::str::contains(
+            "distinct password of 20 characters",
+        ));
+
+    cli_which!(
+        "simple.art",
+        "3HVJP9w1J4veu74PcF9UFAJRU9n2wUGEVB",
+        "-i",
+        "6"
+    )
+    .failure()
+    .stderr(predicate::str::contains("no BIP85 index in 6..1006"));
+
+    // an encrypted key needs the diagram's password
+    cli_which!(
+        "xprv9s21ZrQH143K2e11sHded37jHumTRiKqGpauB1McmiBbxnf4ZZ4rgSRjyPWF4A8gbNFkzsPGZybFBKX7y3fRKgtgqeFdontXTtPRLoAygY5",
+        "6PYKnmJG5BaJWmF31MXUg8YqPZnGF49NxvtdtwDoyhjwrva4gqFCjXmG1J"
+    )
+    .failure()
+    .stderr(predicate::str::contains("give its address instead"));
+}
+
+#[test]
+fn test_which_index_master() {
+    // children of the BIP85 test vectors at index 0
+    const MASTER: &str = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+    cli_which!(MASTER, "_s`{TW89)i4`")
+        .success()
+        .stdout(predicate::str::contains("base85 password of 12 characters"));
+    cli_which!(
+        MASTER,
+        "492db4698cf3b73a5a24998aa3e9d7fa96275d85724a91e71aa2d645442f878555d078fd1f1f67e368976f04137b1f7a0d19232136ca50c44614af72b5582a5c"
+    )
+    .success()
+    .stdout(predicate::str::contains("hex entropy of 64 bytes"));
+
+    // child from stdin, never prompted for without input
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args(["which-index", MASTER, "--child-stdin"])
+        .write_stdin("_s`{TW89)i4`\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("base85 password of 12 characters"));
+    let mut cmd = Command::cargo_bin("artimonist").unwrap();
+    cmd.args(["which-index", MASTER, "--no-input"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("--child-stdin"));
+}